@@ -34,6 +34,11 @@ fn main() {
         talmudifier = talmudifier.log();
     }
 
+    // Report characters that can't be typeset.
+    for missing_glyph in talmudifier.check_glyphs().unwrap() {
+        eprintln!("Missing glyph: {}", missing_glyph);
+    }
+
     // Talmudify.
     let daf = talmudifier.talmudify().unwrap();
 
@@ -1,4 +1,4 @@
-use std::{io, path::PathBuf};
+use std::io;
 
 use thiserror::Error;
 
@@ -15,7 +15,9 @@ pub enum Error {
     #[error("Cosmic font error: {0}")]
     CosmicFont(io::Error),
     #[error("Font not found: {0}")]
-    NoFont(PathBuf),
+    NoFont(String),
+    #[error("Invalid OpenType feature: {0}")]
+    FontFeature(String),
     #[error("TeX fonts error: {0}")]
     TexFonts(io::Error),
     #[error("Error reading config file: {0}")]
@@ -28,4 +30,10 @@ pub enum Error {
     NoColumns,
     #[error("Tried to read a single markdown file but found {0} paragraphs (should be 3).")]
     NumberOfParagraphs(usize),
+    #[error("Invalid length: {0}")]
+    Length(String),
+    #[error("Can't convert a length that is relative to the font size to pts: {0}")]
+    RelativeLength(String),
+    #[error("Unsupported language: {0}")]
+    Language(String),
 }
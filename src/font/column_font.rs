@@ -0,0 +1,28 @@
+use serde::{Deserialize, Serialize};
+
+use super::{fallback::Fallback, font_metrics::FontMetrics, Font};
+
+/// The font of a column, and the column's optional font metrics and fallback fonts.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct ColumnFont {
+    /// The font files or family.
+    #[serde(flatten)]
+    pub font: Font,
+    /// Optional font size and line skip. If None, the page's font metrics are used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metrics: Option<FontMetrics>,
+    /// Fallback fonts that are only used by this column.
+    /// These are tried in order, before the fallback fonts that every column uses.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fallbacks: Vec<Fallback>,
+}
+
+impl From<Font> for ColumnFont {
+    fn from(value: Font) -> Self {
+        Self {
+            font: value,
+            metrics: None,
+            fallbacks: vec![],
+        }
+    }
+}
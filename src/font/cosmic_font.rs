@@ -1,68 +1,312 @@
-use std::{fs::read, io, sync::Arc};
+use std::fs::read;
 
-use cosmic_text::{fontdb::Source, Attrs, AttrsOwned, Family, FontSystem, Metrics};
+use cosmic_text::{
+    fontdb::{FaceInfo, Query, ID},
+    AttrsOwned, CacheKeyFlags, FontSystem, Metrics, Stretch, Style as FontStyle, Weight,
+};
+
+use rustybuzz::{shape, Feature, UnicodeBuffer, Variation};
+
+use crate::{error::Error, span::style::Style};
 
 #[cfg(feature = "default-fonts")]
 use super::default_fonts::*;
-use super::{font_metrics::FontMetrics, font_paths::FontPaths};
+use super::{
+    face::{Face, Faces},
+    font_metrics::FontMetrics,
+    font_paths::FontPaths,
+    shared_font_system::SharedFontSystem,
+    synthetic::Synthetic,
+};
 
+#[derive(Clone)]
 pub struct CosmicFont {
     pub regular: AttrsOwned,
     pub italic: AttrsOwned,
     pub bold: AttrsOwned,
     pub bold_italic: AttrsOwned,
     pub metrics: Metrics,
-    pub font_system: FontSystem,
+    /// The font system that the font is loaded into. This is shared with every other font.
+    pub font_system: SharedFontSystem,
+    /// OpenType features. Cosmic always shapes text with the default features, so see [`CosmicFont::get_feature_scale`].
+    pub features: Vec<Feature>,
+    /// Variable font axes. Cosmic always shapes text with the font's default instance, so see [`CosmicFont::get_feature_scale`].
+    pub variations: Vec<Variation>,
 }
 
 impl CosmicFont {
     pub fn new(
         paths: &FontPaths,
+        faces: &Faces,
         metrics: &FontMetrics,
-        font_system: FontSystem,
-    ) -> Result<Self, io::Error> {
-        let regular = read(&paths.regular)?;
-        let italic = read(&paths.italic)?;
-        let bold = read(&paths.bold)?;
-        let bold_italic = read(&paths.bold_italic)?;
-        Ok(Self::new_from_bytes(
-            regular,
-            italic,
-            bold,
-            bold_italic,
+        font_system: SharedFontSystem,
+    ) -> Result<Self, Error> {
+        let [regular, italic, bold, bold_italic] = [
+            &paths.regular,
+            &paths.italic,
+            &paths.bold,
+            &paths.bold_italic,
+        ]
+        .map(read);
+        Self::new_from_bytes(
+            regular.map_err(Error::CosmicFont)?,
+            italic.map_err(Error::CosmicFont)?,
+            bold.map_err(Error::CosmicFont)?,
+            bold_italic.map_err(Error::CosmicFont)?,
+            faces,
             metrics,
             font_system,
-        ))
+        )
     }
 
-    fn new_from_bytes(
+    /// Create a font from a system font family.
+    /// `styles` are the names of the regular, italic, bold, and bold italic styles.
+    pub fn new_from_family(
+        family: &str,
+        styles: [&str; 4],
+        metrics: &FontMetrics,
+        font_system: SharedFontSystem,
+    ) -> Result<Self, Error> {
+        let mut database = font_system.lock();
+        let [regular, italic, bold, bold_italic] = styles.map(|style| {
+            Self::get_system_font(family, style, &database.font_system)
+                .map(|id| database.get_attrs(id))
+        });
+        drop(database);
+        Ok(Self {
+            regular: regular?,
+            italic: italic?,
+            bold: bold?,
+            bold_italic: bold_italic?,
+            metrics: metrics.into(),
+            font_system,
+            features: vec![],
+            variations: vec![],
+        })
+    }
+
+    pub(crate) fn new_from_bytes(
         regular: Vec<u8>,
         italic: Vec<u8>,
         bold: Vec<u8>,
         bold_italic: Vec<u8>,
+        faces: &Faces,
         metrics: &FontMetrics,
-        mut font_system: FontSystem,
-    ) -> Self {
-        let regular = Self::get_font(regular, &mut font_system);
-        let italic = Self::get_font(italic, &mut font_system);
-        let bold = Self::get_font(bold, &mut font_system);
-        let bold_italic = Self::get_font(bold_italic, &mut font_system);
-        Self {
+        font_system: SharedFontSystem,
+    ) -> Result<Self, Error> {
+        let [regular_face, italic_face, bold_face, bold_italic_face] = faces.get();
+        let mut database = font_system.lock();
+        let mut get_font = |font: Vec<u8>, face: Option<&Face>| {
+            let ids = database.load(font);
+            let font_id = Face::select(face, database.font_system.db(), &ids)?;
+            Ok(database.get_attrs(font_id))
+        };
+        let regular = get_font(regular, regular_face)?;
+        let italic = get_font(italic, italic_face)?;
+        let bold = get_font(bold, bold_face)?;
+        let bold_italic = get_font(bold_italic, bold_italic_face)?;
+        drop(database);
+        Ok(Self {
             regular,
             italic,
             bold,
             bold_italic,
             metrics: metrics.into(),
             font_system,
+            features: vec![],
+            variations: vec![],
+        })
+    }
+
+    /// Synthesize missing styles. `styles` are the regular, italic, bold, and bold italic styles.
+    ///
+    /// Slanted styles are skewed like Cosmic's synthetic italic.
+    /// Cosmic can't embolden glyphs, but neither `FakeBold` nor `FakeSlant` change the advance widths, so the base style is measured as-is.
+    pub(crate) fn set_synthetic(&mut self, styles: &[Synthetic; 4]) {
+        for (attrs, style) in [
+            &mut self.regular,
+            &mut self.italic,
+            &mut self.bold,
+            &mut self.bold_italic,
+        ]
+        .into_iter()
+        .zip(styles)
+        {
+            if style.slant {
+                attrs.cache_key_flags |= CacheKeyFlags::FAKE_ITALIC;
+            }
+        }
+    }
+
+    /// Returns true if the face that is used for `style` has a glyph for `c`.
+    pub fn has_glyph(&self, style: Style, c: char) -> bool {
+        let attrs = style.attrs(self);
+        let mut database = self.font_system.lock();
+        match Self::get_face_id(&attrs, &database.font_system) {
+            Some(id) => database
+                .font_system
+                .get_font(id)
+                .is_some_and(|font| font.rustybuzz().glyph_index(c).is_some()),
+            None => false,
+        }
+    }
+
+    /// Parse OpenType features, e.g. `"onum"`, `"-liga"`, or `"salt=2"`, and variable font axes, e.g. `"wght=700"`.
+    ///
+    /// Features that XeTeX can't apply are rejected, i.e. features that only apply to part of the text, e.g. `"liga[3:5]"`.
+    pub(crate) fn parse_features(
+        features: &[String],
+    ) -> Result<(Vec<Feature>, Vec<Variation>), Error> {
+        let mut parsed = (vec![], vec![]);
+        for f in features {
+            if Self::is_axis(f) {
+                match f.parse::<Variation>() {
+                    Ok(variation) => parsed.1.push(variation),
+                    Err(_) => return Err(Error::FontFeature(f.clone())),
+                }
+            } else {
+                match f.parse::<Feature>() {
+                    Ok(feature) if feature.start == 0 && feature.end == u32::MAX => {
+                        parsed.0.push(feature)
+                    }
+                    _ => return Err(Error::FontFeature(f.clone())),
+                }
+            }
         }
+        Ok(parsed)
     }
 
-    fn get_font(font: Vec<u8>, font_system: &mut FontSystem) -> AttrsOwned {
-        let font_id = font_system
-            .db_mut()
-            .load_font_source(Source::Binary(Arc::new(font)))[0];
-        let family_name = &font_system.db().face(font_id).unwrap().families[0].0;
-        AttrsOwned::new(Attrs::new().family(Family::Name(family_name)))
+    /// Returns true if `feature` sets a variable font axis, e.g. `"wght=700"`.
+    /// Axes are the registered axes and custom axes, whose tags start with an uppercase letter.
+    pub(crate) fn is_axis(feature: &str) -> bool {
+        // Registered variable font axes.
+        const AXES: [&str; 5] = ["ital", "opsz", "slnt", "wdth", "wght"];
+
+        match feature.split_once('=') {
+            Some((tag, _)) => {
+                AXES.contains(&tag) || tag.starts_with(|c: char| c.is_ascii_uppercase())
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the ratio of the width of `text` shaped with the font's OpenType features and variable font axes to its width shaped with the default features and axes.
+    ///
+    /// Cosmic Text always shapes text with the default features and the font's default instance, so text whose width is changed by the features or axes, e.g. with `"-liga"`, `"onum"`, or `"wdth=75"`, is scaled by this ratio when it is measured.
+    /// Returns None if the font doesn't have features or axes, or if they don't change the width of `text`.
+    pub fn get_feature_scale(&self, style: Style, text: &str) -> Option<f32> {
+        if self.features.is_empty() && self.variations.is_empty() {
+            return None;
+        }
+        let attrs = style.attrs(self);
+        let mut database = self.font_system.lock();
+        let id = Self::get_face_id(&attrs, &database.font_system)?;
+        let font = database.font_system.get_font(id)?;
+        let get_width = |face: &rustybuzz::Face, features: &[Feature]| {
+            let mut buffer = UnicodeBuffer::new();
+            buffer.push_str(text);
+            buffer.guess_segment_properties();
+            shape(face, features, buffer)
+                .glyph_positions()
+                .iter()
+                .map(|position| position.x_advance)
+                .sum::<i32>()
+        };
+        let width = get_width(font.rustybuzz(), &[]);
+        let mut face = font.rustybuzz().clone();
+        face.set_variations(&self.variations);
+        let feature_width = get_width(&face, &self.features);
+        if width == 0 || width == feature_width {
+            None
+        } else {
+            Some(feature_width as f32 / width as f32)
+        }
+    }
+
+    /// Returns the ID of the face that best matches `attrs`.
+    fn get_face_id(attrs: &AttrsOwned, font_system: &FontSystem) -> Option<ID> {
+        let attrs = attrs.as_attrs();
+        font_system.db().query(&Query {
+            families: &[attrs.family],
+            weight: attrs.weight,
+            stretch: attrs.stretch,
+            style: attrs.style,
+        })
+    }
+
+    /// Find a face in the system fonts that matches the family and style names, and return its ID.
+    fn get_system_font(family: &str, style: &str, font_system: &FontSystem) -> Result<ID, Error> {
+        let faces = font_system
+            .db()
+            .faces()
+            .filter(|face| {
+                face.families
+                    .iter()
+                    .any(|(name, _)| name.eq_ignore_ascii_case(family))
+            })
+            .collect::<Vec<&FaceInfo>>();
+        let face = match Self::parse_style(style) {
+            // Match the weight and slant. Prefer faces that aren't condensed or expanded.
+            Some((weight, italic)) => faces
+                .into_iter()
+                .filter(|face| face.weight == weight && (face.style != FontStyle::Normal) == italic)
+                .min_by_key(|face| face.stretch != Stretch::Normal),
+            // Fall back to the end of the PostScript name, e.g. `FrankRuehlCLM-MediumOblique`.
+            None => faces
+                .into_iter()
+                .find(|face| Self::is_post_script_style(face, style)),
+        };
+        match face {
+            Some(face) => Ok(face.id),
+            None => Err(Error::NoFont(format!("{} {}", family, style))),
+        }
+    }
+
+    /// Parse a style name, e.g. `Semibold Italic`, into a weight and whether the style is italic or oblique.
+    /// Returns None if the style name isn't recognized.
+    fn parse_style(style: &str) -> Option<(Weight, bool)> {
+        let style = Self::normalize_style(style);
+        let mut weight = style.as_str();
+        let mut italic = false;
+        for suffix in ["italic", "oblique", "it"] {
+            if let Some(w) = weight.strip_suffix(suffix) {
+                weight = w;
+                italic = true;
+                break;
+            }
+        }
+        let weight = match weight {
+            "" | "regular" | "roman" | "book" | "normal" | "upright" => Weight::NORMAL,
+            "thin" | "hairline" => Weight::THIN,
+            "extralight" | "ultralight" => Weight::EXTRA_LIGHT,
+            "light" => Weight::LIGHT,
+            "medium" => Weight::MEDIUM,
+            "semibold" | "demibold" | "demi" => Weight::SEMIBOLD,
+            "bold" => Weight::BOLD,
+            "extrabold" | "ultrabold" => Weight::EXTRA_BOLD,
+            "black" | "heavy" => Weight::BLACK,
+            _ => return None,
+        };
+        Some((weight, italic))
+    }
+
+    /// Returns true if the end of the face's PostScript name is `style`.
+    fn is_post_script_style(face: &FaceInfo, style: &str) -> bool {
+        match face.post_script_name.rsplit_once('-') {
+            Some((_, face_style)) => {
+                Self::normalize_style(face_style) == Self::normalize_style(style)
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the lowercase alphanumeric characters of a style name.
+    fn normalize_style(style: &str) -> String {
+        style
+            .chars()
+            .filter(|c| c.is_alphanumeric())
+            .collect::<String>()
+            .to_lowercase()
     }
 
     #[cfg(feature = "default-fonts")]
@@ -72,9 +316,11 @@ impl CosmicFont {
             IM_FELL_ITALIC.to_vec(),
             IM_FELL_BOLD.to_vec(),
             IM_FELL_BOLD.to_vec(),
+            &Faces::default(),
             &FontMetrics::default(),
-            FontSystem::new(),
+            SharedFontSystem::default(),
         )
+        .unwrap()
     }
 
     #[cfg(feature = "default-fonts")]
@@ -84,9 +330,11 @@ impl CosmicFont {
             EB_GARAMOND_ITALIC.to_vec(),
             EB_GARAMOND_BOLD.to_vec(),
             EB_GARAMOND_BOLD_ITALIC.to_vec(),
+            &Faces::default(),
             &FontMetrics::default(),
-            FontSystem::new(),
+            SharedFontSystem::default(),
         )
+        .unwrap()
     }
 
     #[cfg(feature = "default-fonts")]
@@ -96,8 +344,69 @@ impl CosmicFont {
             AVERIA_ITALIC.to_vec(),
             AVERIA_BOLD.to_vec(),
             AVERIA_BOLD_ITALIC.to_vec(),
+            &Faces::default(),
             &FontMetrics::default(),
-            FontSystem::new(),
+            SharedFontSystem::default(),
         )
+        .unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmic_text::Weight;
+
+    #[cfg(feature = "default-fonts")]
+    use crate::span::style::Style;
+
+    use super::CosmicFont;
+
+    #[test]
+    #[cfg(feature = "default-fonts")]
+    fn test_features() {
+        let features = ["onum", "-liga", "salt=2", "wght=700", "GRAD=-50"].map(|f| f.to_string());
+        let (features, variations) = CosmicFont::parse_features(&features).unwrap();
+        assert_eq!(features.len(), 3);
+        assert_eq!(features[1].value, 0);
+        assert_eq!(variations.len(), 2);
+        assert_eq!(variations[0].value, 700.);
+        for feature in ["liga[3:5]", "not a feature", "wght=bold"] {
+            assert!(CosmicFont::parse_features(&[feature.to_string()]).is_err());
+        }
+
+        // Disabling ligatures changes the width of "ff".
+        let mut font = CosmicFont::default_center();
+        assert_eq!(font.get_feature_scale(Style::REGULAR, "offer"), None);
+        // The font isn't variable, so an axis doesn't change its width.
+        font.variations = CosmicFont::parse_features(&["wght=700".to_string()])
+            .unwrap()
+            .1;
+        assert_eq!(font.get_feature_scale(Style::REGULAR, "offer"), None);
+        font.variations.clear();
+        font.features = CosmicFont::parse_features(&["-liga".to_string()])
+            .unwrap()
+            .0;
+        assert!(font
+            .get_feature_scale(Style::REGULAR, "offer")
+            .is_some_and(|scale| scale > 1.));
+        assert_eq!(font.get_feature_scale(Style::REGULAR, "talmud"), None);
+    }
+
+    #[test]
+    fn test_parse_style() {
+        for (style, expected) in [
+            ("Regular", Some((Weight::NORMAL, false))),
+            ("Roman", Some((Weight::NORMAL, false))),
+            ("Italic", Some((Weight::NORMAL, true))),
+            ("It", Some((Weight::NORMAL, true))),
+            ("Bold", Some((Weight::BOLD, false))),
+            ("Bold Italic", Some((Weight::BOLD, true))),
+            ("BoldOblique", Some((Weight::BOLD, true))),
+            ("SemiboldIt", Some((Weight::SEMIBOLD, true))),
+            ("Medium Oblique", Some((Weight::MEDIUM, true))),
+            ("Condensed Bold", None),
+        ] {
+            assert_eq!(CosmicFont::parse_style(style), expected, "{}", style);
+        }
     }
 }
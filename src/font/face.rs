@@ -0,0 +1,100 @@
+use cosmic_text::fontdb::{Database, Source, ID};
+use serde::{Deserialize, Serialize};
+
+use crate::error::Error;
+
+/// Selects a face in a font file that has more than one face, e.g. a .ttc collection.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum Face {
+    /// The index of the face in the file, e.g. `1`.
+    Index(u32),
+    /// The PostScript name of the face, e.g. `"FrankRuehlCLM-Bold"`.
+    PostScriptName(String),
+}
+
+impl Face {
+    /// Returns the ID of the face in `ids`, which are the faces that were loaded from a single source.
+    /// If `face` is None, this returns the first face.
+    pub(crate) fn select(face: Option<&Self>, db: &Database, ids: &[ID]) -> Result<ID, Error> {
+        match face {
+            None => ids.first().copied(),
+            Some(face) => ids.iter().copied().find(|id| {
+                db.face(*id).is_some_and(|info| match face {
+                    Self::Index(index) => info.index == *index,
+                    Self::PostScriptName(name) => info.post_script_name == *name,
+                })
+            }),
+        }
+        .ok_or_else(|| Error::NoFont(format!("{:?}", face)))
+    }
+
+    /// Returns the index of the face in a font source.
+    /// PostScript names are resolved by reading the font.
+    pub(crate) fn get_index(&self, source: Source) -> Result<u32, Error> {
+        match self {
+            Self::Index(index) => Ok(*index),
+            Self::PostScriptName(_) => {
+                let mut db = Database::new();
+                let ids = db.load_font_source(source);
+                let id = Self::select(Some(self), &db, &ids)?;
+                Ok(db.face(id).unwrap().index)
+            }
+        }
+    }
+}
+
+/// The faces of each style in font files that have more than one face.
+/// If a style's face is None, the first face in the file is used.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct Faces {
+    #[serde(default)]
+    pub regular: Option<Face>,
+    #[serde(default)]
+    pub italic: Option<Face>,
+    #[serde(default)]
+    pub bold: Option<Face>,
+    #[serde(default)]
+    pub bold_italic: Option<Face>,
+}
+
+impl Faces {
+    /// Returns the regular, italic, bold, and bold italic faces.
+    pub(crate) fn get(&self) -> [Option<&Face>; 4] {
+        [
+            self.regular.as_ref(),
+            self.italic.as_ref(),
+            self.bold.as_ref(),
+            self.bold_italic.as_ref(),
+        ]
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.get().iter().all(|face| face.is_none())
+    }
+}
+
+#[cfg(all(test, feature = "default-fonts"))]
+mod tests {
+    use std::sync::Arc;
+
+    use cosmic_text::fontdb::Source;
+
+    use super::Face;
+    use crate::font::default_fonts::EB_GARAMOND_BOLD;
+
+    #[test]
+    fn test_face() {
+        let source = || Source::Binary(Arc::new(EB_GARAMOND_BOLD.to_vec()));
+        assert_eq!(Face::Index(0).get_index(source()).unwrap(), 0);
+        assert_eq!(
+            Face::PostScriptName("EBGaramond-Bold".to_string())
+                .get_index(source())
+                .unwrap(),
+            0
+        );
+        assert!(Face::PostScriptName("EBGaramond-Black".to_string())
+            .get_index(source())
+            .is_err());
+    }
+}
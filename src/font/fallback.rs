@@ -0,0 +1,125 @@
+use std::ops::RangeInclusive;
+
+use serde::{Deserialize, Serialize};
+
+use super::{cosmic_font::CosmicFont, Font};
+
+/// A script, i.e. a writing system.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, Eq, PartialEq)]
+pub enum Script {
+    Hebrew,
+    Greek,
+    Latin,
+    Cyrillic,
+    Arabic,
+    Syriac,
+}
+
+impl Script {
+    /// Returns the Unicode blocks of the letters of the script.
+    const fn ranges(&self) -> &'static [RangeInclusive<char>] {
+        match self {
+            Self::Hebrew => &['\u{0590}'..='\u{05FF}', '\u{FB1D}'..='\u{FB4F}'],
+            Self::Greek => &['\u{0370}'..='\u{03FF}', '\u{1F00}'..='\u{1FFF}'],
+            Self::Latin => &[
+                'A'..='Z',
+                'a'..='z',
+                '\u{00C0}'..='\u{024F}',
+                '\u{1E00}'..='\u{1EFF}',
+            ],
+            Self::Cyrillic => &['\u{0400}'..='\u{04FF}'],
+            Self::Arabic => &['\u{0600}'..='\u{06FF}'],
+            Self::Syriac => &['\u{0700}'..='\u{074F}'],
+        }
+    }
+}
+
+/// A range of characters that are always set in a fallback font.
+#[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq)]
+#[serde(untagged)]
+pub enum CharRange {
+    /// Every letter of a script, e.g. `"Greek"`.
+    Script(Script),
+    /// An inclusive range of characters, e.g. `["α", "ω"]`.
+    Range(char, char),
+}
+
+impl CharRange {
+    /// Returns true if `c` is in the range.
+    pub fn contains(&self, c: char) -> bool {
+        match self {
+            Self::Script(script) => script.ranges().iter().any(|range| range.contains(&c)),
+            Self::Range(start, end) => (*start..=*end).contains(&c),
+        }
+    }
+}
+
+/// A fallback font.
+///
+/// If `ranges` is empty, the fallback font is used for characters that the column's font doesn't have.
+/// Otherwise, the fallback font is used for every character in `ranges`, even if the column's font has it.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct Fallback {
+    /// The font files or family.
+    #[serde(flatten)]
+    pub font: Font,
+    /// The characters that are always set in this font.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ranges: Vec<CharRange>,
+}
+
+impl Fallback {
+    pub fn new(font: Font, ranges: Vec<CharRange>) -> Self {
+        Self { font, ranges }
+    }
+}
+
+impl From<Font> for Fallback {
+    fn from(value: Font) -> Self {
+        Self::new(value, vec![])
+    }
+}
+
+/// A fallback font that has been loaded into Cosmic.
+#[derive(Clone)]
+pub(crate) struct CosmicFallback {
+    pub font: CosmicFont,
+    pub ranges: Vec<CharRange>,
+}
+
+impl CosmicFallback {
+    /// Returns true if `c` is always set in this font.
+    pub(crate) fn is_forced(&self, c: char) -> bool {
+        self.ranges.iter().any(|range| range.contains(c))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CharRange, Fallback, Script};
+
+    #[test]
+    fn test_char_range() {
+        let hebrew = CharRange::Script(Script::Hebrew);
+        assert!(hebrew.contains('ש'));
+        assert!(!hebrew.contains('s'));
+        assert!(CharRange::Script(Script::Latin).contains('é'));
+        let range = CharRange::Range('α', 'ω');
+        assert!(range.contains('β'));
+        assert!(!range.contains('Ω'));
+        // Fallbacks can be a font with or without ranges.
+        let fallback: Fallback = serde_json::from_str(
+            r#"{"family": "EB Garamond", "regular": "Regular", "italic": null, "bold": null, "bold_italic": null, "ranges": ["Greek", ["a", "z"]]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            fallback.ranges,
+            vec![CharRange::Script(Script::Greek), CharRange::Range('a', 'z')]
+        );
+        let fallback: Fallback = serde_json::from_str(
+            r#"{"family": "EB Garamond", "regular": "Regular", "italic": null, "bold": null, "bold_italic": null}"#,
+        )
+        .unwrap();
+        assert!(fallback.ranges.is_empty());
+    }
+}
@@ -0,0 +1,104 @@
+use std::{
+    collections::hash_map::RandomState,
+    env::temp_dir,
+    fs::{create_dir_all, remove_dir_all, write, DirBuilder},
+    hash::{BuildHasher, Hasher},
+    io,
+    path::PathBuf,
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::SystemTime,
+};
+
+/// The number of font directories that this process has created.
+static NUM_DIRECTORIES: AtomicUsize = AtomicUsize::new(0);
+
+/// The number of times to try to create a directory with a new random name.
+const MAX_ATTEMPTS: usize = 100;
+
+/// XeTeX requires fonts to be saved to a path (rather than exist in memory).
+/// This is a unique temporary directory that fonts stored in memory can be written to.
+/// The name of the directory ends with a random suffix. The directory is always newly created, never reused:
+/// if a file or directory with that name already exists, e.g. from a crashed process or another container, a new name is tried.
+/// On Unix, only the current user can access the directory.
+/// When this struct is dropped, the directory is deleted.
+pub struct FontDirectory {
+    pub(crate) path: PathBuf,
+}
+
+impl FontDirectory {
+    /// The filenames of the regular, italic, bold, and bold italic fonts.
+    pub(crate) const FILENAMES: [&str; 4] =
+        ["regular.ttf", "italic.ttf", "bold.ttf", "bold_italic.ttf"];
+
+    pub fn new() -> Result<Self, io::Error> {
+        let mut builder = DirBuilder::new();
+        #[cfg(unix)]
+        std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+        let temp_dir = temp_dir();
+        for _ in 0..MAX_ATTEMPTS {
+            let path = temp_dir.join(format!("talmudifier_fonts_{:016x}", Self::random_suffix()));
+            // Unlike `create_dir_all`, this fails if the path already exists.
+            match builder.create(&path) {
+                Ok(()) => return Ok(Self { path }),
+                Err(error) if error.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(error) => return Err(error),
+            }
+        }
+        Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            "failed to create a unique font directory",
+        ))
+    }
+
+    /// Returns a random number.
+    /// `RandomState` is randomly seeded, and the process ID, counter, and time make each number unique.
+    fn random_suffix() -> u64 {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u32(process::id());
+        hasher.write_usize(NUM_DIRECTORIES.fetch_add(1, Ordering::Relaxed));
+        if let Ok(time) = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH) {
+            hasher.write_u128(time.as_nanos());
+        }
+        hasher.finish()
+    }
+
+    /// Write the regular, italic, bold, and bold italic fonts to a folder in this directory.
+    /// Returns the path to the folder.
+    pub(crate) fn write_fonts(
+        &self,
+        folder: &str,
+        fonts: [&[u8]; 4],
+    ) -> Result<PathBuf, io::Error> {
+        let directory = self.path.join(folder);
+        create_dir_all(&directory)?;
+        for (filename, font) in Self::FILENAMES.iter().zip(fonts) {
+            write(directory.join(filename), font)?;
+        }
+        Ok(directory)
+    }
+}
+
+impl Drop for FontDirectory {
+    fn drop(&mut self) {
+        let _ = remove_dir_all(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FontDirectory;
+
+    #[test]
+    fn test_font_directory() {
+        let a = FontDirectory::new().unwrap();
+        let b = FontDirectory::new().unwrap();
+        assert_ne!(a.path, b.path);
+        let folder = a.write_fonts("left", [&[0], &[1], &[2], &[3]]).unwrap();
+        assert!(folder.join("bold_italic.ttf").exists());
+        let path = a.path.clone();
+        drop(a);
+        assert!(!path.exists());
+        assert!(b.path.exists());
+    }
+}
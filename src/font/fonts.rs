@@ -1,21 +1,52 @@
+use std::collections::BTreeMap;
 #[cfg(feature = "default-fonts")]
 use std::{path::PathBuf, str::FromStr};
 
 use serde::{Deserialize, Serialize};
 
-use crate::{error::Error, prelude::FontMetrics};
+use crate::{
+    error::Error,
+    prelude::FontMetrics,
+    span::{language::Language, style::Style},
+    table::position::Position,
+};
 
-use super::{cosmic_fonts::CosmicFonts, tex_fonts::TexFonts, Font};
+use super::{
+    column_font::ColumnFont,
+    cosmic_font::CosmicFont,
+    cosmic_fonts::CosmicFonts,
+    fallback::{CosmicFallback, Fallback},
+    styled_font::StyledFont,
+    tex_font::TexFont,
+    tex_fonts::TexFonts,
+    Font,
+};
 
 #[cfg(feature = "default-fonts")]
 use super::{default_tex_fonts::DefaultTexFonts, DEFAULT_ROOT_DIRECTORY};
 
 /// Fonts for the left, center, and right columns.
-#[derive(Deserialize, Serialize)]
+#[derive(Clone, Deserialize, Serialize)]
 pub struct Fonts {
-    pub(super) left: Font,
-    pub(super) center: Font,
-    pub(super) right: Font,
+    pub(super) left: ColumnFont,
+    pub(super) center: ColumnFont,
+    pub(super) right: ColumnFont,
+    /// Optional fonts for text that is tagged with a language, e.g. `<span lang="he">`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub(super) languages: BTreeMap<Language, Font>,
+    /// Fonts that are used, in order, for characters that a column's font doesn't have.
+    /// These are used by every column, after the column's own fallback fonts.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(super) fallbacks: Vec<Fallback>,
+    /// An optional font for the title. If None, the title is set in the center column's font.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(super) title: Option<StyledFont>,
+    /// An optional font for marginalia. If None, marginalia are set in a tiny version of the column's font.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(super) marginalia: Option<StyledFont>,
+    /// An optional font for running headers and footers. If None, the center column's font is used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(super) running_heads: Option<StyledFont>,
     /// This is set in `default()` and it's used to determine how to create the `TexFonts` and `CosmicFonts`.
     #[cfg_attr(feature = "default-fonts", serde(skip))]
     default: bool,
@@ -24,18 +55,243 @@ pub struct Fonts {
 impl Fonts {
     pub fn new(left: Font, center: Font, right: Font) -> Self {
         Self {
-            left,
-            center,
-            right,
+            left: left.into(),
+            center: center.into(),
+            right: right.into(),
+            languages: BTreeMap::default(),
+            fallbacks: vec![],
+            title: None,
+            marginalia: None,
+            running_heads: None,
             default: false,
         }
     }
 
+    /// Add a fallback font for characters that the column fonts don't have.
+    /// Fallback fonts are tried in the order that they're added.
+    pub fn fallback<F: Into<Fallback>>(mut self, fallback: F) -> Self {
+        self.fallbacks.push(fallback.into());
+        self
+    }
+
+    /// Set the fallback fonts per column.
+    /// A column's fallback fonts are tried in order, before the fallback fonts that every column uses.
+    pub fn column_fallbacks(
+        mut self,
+        left: Vec<Fallback>,
+        center: Vec<Fallback>,
+        right: Vec<Fallback>,
+    ) -> Self {
+        self.left.fallbacks = left;
+        self.center.fallbacks = center;
+        self.right.fallbacks = right;
+        self
+    }
+
+    /// Set the font size and line skip per column.
+    /// If a column's metrics are None, the page's font metrics are used.
+    pub fn column_metrics(
+        mut self,
+        left: Option<FontMetrics>,
+        center: Option<FontMetrics>,
+        right: Option<FontMetrics>,
+    ) -> Self {
+        self.left.metrics = left;
+        self.center.metrics = center;
+        self.right.metrics = right;
+        self
+    }
+
+    /// Set the font of the title.
+    pub fn title(mut self, font: StyledFont) -> Self {
+        self.title = Some(font);
+        self
+    }
+
+    /// Set the font of marginalia.
+    pub fn marginalia(mut self, font: StyledFont) -> Self {
+        self.marginalia = Some(font);
+        self
+    }
+
+    /// Set the font of running headers and footers.
+    pub fn running_heads(mut self, font: StyledFont) -> Self {
+        self.running_heads = Some(font);
+        self
+    }
+
+    /// Set the font for text tagged with `language`.
+    pub fn language(mut self, language: Language, font: Font) -> Self {
+        self.languages.insert(language, font);
+        self
+    }
+
+    /// Convert the language fonts to TexFonts.
+    pub(super) fn language_tex_fonts(&self) -> Result<Vec<TexFont>, Error> {
+        self.languages
+            .iter()
+            .map(|(language, font)| font.to_tex(language.font_command()))
+            .collect()
+    }
+
+    /// Convert the fallback fonts that every column uses to TexFonts.
+    pub(super) fn fallback_tex_fonts(&self) -> Result<Vec<TexFont>, Error> {
+        Self::get_fallback_tex_fonts("", &self.fallbacks)
+    }
+
+    /// Convert the fallback fonts of the left, center, and right columns to TexFonts.
+    pub(super) fn column_fallback_tex_fonts(&self) -> Result<[Vec<TexFont>; 3], Error> {
+        Ok([
+            Self::get_fallback_tex_fonts("left", &self.left.fallbacks)?,
+            Self::get_fallback_tex_fonts("center", &self.center.fallbacks)?,
+            Self::get_fallback_tex_fonts("right", &self.right.fallbacks)?,
+        ])
+    }
+
+    /// Convert the title font to a TexFont.
+    pub(super) fn title_tex_font(&self) -> Result<Option<TexFont>, Error> {
+        self.title
+            .as_ref()
+            .map(|font| font.to_tex("titlefont"))
+            .transpose()
+    }
+
+    /// Returns the Cosmic font of the marginalia and the style that marginalia are set in, or None if there is no marginalia font.
+    pub(crate) fn marginalia_cosmic_font(&self) -> Result<Option<(CosmicFont, Style)>, Error> {
+        self.marginalia
+            .as_ref()
+            .map(|font| Ok((font.to_cosmic()?, font.style.get_style())))
+            .transpose()
+    }
+
+    /// Convert the marginalia font to a TexFont.
+    pub(super) fn marginalia_tex_font(&self) -> Result<Option<TexFont>, Error> {
+        self.marginalia
+            .as_ref()
+            .map(|font| font.to_tex("marginaliafont"))
+            .transpose()
+    }
+
+    /// Convert the running headers and footers font to a TexFont.
+    pub(super) fn running_heads_tex_font(&self) -> Result<Option<TexFont>, Error> {
+        self.running_heads
+            .as_ref()
+            .map(|font| font.to_tex("runningheadfont"))
+            .transpose()
+    }
+
+    /// Convert the fallback chain of the column at `position` to CosmicFonts.
+    /// The chain is the column's fallback fonts followed by the fallback fonts that every column uses.
+    pub(crate) fn fallback_cosmic_fonts(
+        &self,
+        position: Position,
+        font_metrics: &FontMetrics,
+    ) -> Result<Vec<CosmicFallback>, Error> {
+        let metrics = self.get_column_metrics(position, font_metrics);
+        self.get_column(position)
+            .fallbacks
+            .iter()
+            .chain(self.fallbacks.iter())
+            .map(|fallback| {
+                Ok(CosmicFallback {
+                    font: fallback.font.to_cosmic(metrics)?,
+                    ranges: fallback.ranges.clone(),
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the languages that have their own fonts.
+    pub(crate) fn get_font_languages(&self) -> Vec<Language> {
+        self.languages.keys().copied().collect()
+    }
+
+    /// Convert fallback fonts to TexFonts. Each command is `prefix` plus the fallback name.
+    fn get_fallback_tex_fonts(prefix: &str, fallbacks: &[Fallback]) -> Result<Vec<TexFont>, Error> {
+        fallbacks
+            .iter()
+            .enumerate()
+            .map(|(i, fallback)| {
+                fallback
+                    .font
+                    .to_tex(&format!("{}{}", prefix, Self::get_fallback_name(i)))
+            })
+            .collect()
+    }
+
+    /// Returns the name of a fallback font's command, e.g. `fallbackfonta`.
+    /// TeX commands can't have digits, so the index is converted to letters: `a` to `z`, then `aa`, `ab`, etc.
+    fn get_fallback_name(index: usize) -> String {
+        let mut letters = vec![];
+        let mut n = index + 1;
+        while n > 0 {
+            n -= 1;
+            letters.push((b'a' + (n % 26) as u8) as char);
+            n /= 26;
+        }
+        format!("fallbackfont{}", letters.iter().rev().collect::<String>())
+    }
+
+    /// Returns the font of the column at `position`.
+    fn get_column(&self, position: Position) -> &ColumnFont {
+        match position {
+            Position::Left => &self.left,
+            Position::Center => &self.center,
+            Position::Right => &self.right,
+        }
+    }
+
+    /// Returns a copy of the fonts in which each column's line skip is `skip`, i.e. each column is on the same baseline grid.
+    pub(crate) fn snap_to_grid(&self, skip: f32) -> Self {
+        let mut fonts = self.clone();
+        for metrics in [&mut fonts.left, &mut fonts.center, &mut fonts.right]
+            .into_iter()
+            .filter_map(|column| column.metrics.as_mut())
+        {
+            metrics.skip = skip;
+        }
+        fonts
+    }
+
+    /// Returns the font metrics of the column at `position`, or `font_metrics` if the column doesn't have its own.
+    fn get_column_metrics<'f>(
+        &'f self,
+        position: Position,
+        font_metrics: &'f FontMetrics,
+    ) -> &'f FontMetrics {
+        self.get_column(position)
+            .metrics
+            .as_ref()
+            .unwrap_or(font_metrics)
+    }
+
+    /// Set the per-column font size and line skip of the TeX font commands.
+    fn set_tex_metrics(&self, tex_fonts: &mut TexFonts) {
+        for (tex_font, position) in [
+            (&mut tex_fonts.left, Position::Left),
+            (&mut tex_fonts.center, Position::Center),
+            (&mut tex_fonts.right, Position::Right),
+        ] {
+            if let Some(metrics) = &self.get_column(position).metrics {
+                tex_font.set_metrics(metrics);
+            }
+        }
+    }
+
     fn get_cosmic_fonts_internal(&self, font_metrics: &FontMetrics) -> Result<CosmicFonts, Error> {
         Ok(CosmicFonts {
-            left: self.left.to_cosmic(font_metrics)?,
-            center: self.center.to_cosmic(font_metrics)?,
-            right: self.right.to_cosmic(font_metrics)?,
+            left: self
+                .left
+                .font
+                .to_cosmic(self.get_column_metrics(Position::Left, font_metrics))?,
+            center: self
+                .center
+                .font
+                .to_cosmic(self.get_column_metrics(Position::Center, font_metrics))?,
+            right: self
+                .right
+                .font
+                .to_cosmic(self.get_column_metrics(Position::Right, font_metrics))?,
         })
     }
 }
@@ -44,7 +300,15 @@ impl Fonts {
 impl Fonts {
     pub(crate) fn cosmic_fonts(&self, font_metrics: &FontMetrics) -> Result<CosmicFonts, Error> {
         if self.default {
-            Ok(CosmicFonts::default())
+            let mut cosmic_fonts = CosmicFonts::default();
+            for (cosmic_font, position) in [
+                (&mut cosmic_fonts.left, Position::Left),
+                (&mut cosmic_fonts.center, Position::Center),
+                (&mut cosmic_fonts.right, Position::Right),
+            ] {
+                cosmic_font.metrics = self.get_column_metrics(position, font_metrics).into();
+            }
+            Ok(cosmic_fonts)
         } else {
             self.get_cosmic_fonts_internal(font_metrics)
         }
@@ -55,11 +319,23 @@ impl Fonts {
         // Get default fonts.
         if self.default {
             match DefaultTexFonts::new() {
-                Ok(default_tex_fonts) => Ok(default_tex_fonts.into()),
+                Ok(default_tex_fonts) => {
+                    let mut tex_fonts: TexFonts = default_tex_fonts.into();
+                    tex_fonts.languages = self.language_tex_fonts()?;
+                    tex_fonts.fallbacks = self.fallback_tex_fonts()?;
+                    tex_fonts.column_fallbacks = self.column_fallback_tex_fonts()?;
+                    tex_fonts.title = self.title_tex_font()?;
+                    tex_fonts.marginalia = self.marginalia_tex_font()?;
+                    tex_fonts.running_heads = self.running_heads_tex_font()?;
+                    self.set_tex_metrics(&mut tex_fonts);
+                    Ok(tex_fonts)
+                }
                 Err(error) => Err(Error::TexFonts(error)),
             }
         } else {
-            Ok(self.into())
+            let mut tex_fonts: TexFonts = self.try_into()?;
+            self.set_tex_metrics(&mut tex_fonts);
+            Ok(tex_fonts)
         }
     }
 }
@@ -71,7 +347,9 @@ impl Fonts {
     }
 
     pub(crate) fn tex_fonts(&self) -> Result<TexFonts, Error> {
-        Ok(self.into())
+        let mut tex_fonts: TexFonts = self.try_into()?;
+        self.set_tex_metrics(&mut tex_fonts);
+        Ok(tex_fonts)
     }
 }
 
@@ -80,10 +358,55 @@ impl Default for Fonts {
     fn default() -> Self {
         let directory = PathBuf::from_str(DEFAULT_ROOT_DIRECTORY).unwrap();
         Self {
-            left: Font::new(&directory, "left"),
-            center: Font::new(&directory, "center"),
-            right: Font::new(&directory, "right"),
+            left: Font::new(&directory, "left").into(),
+            center: Font::new(&directory, "center").into(),
+            right: Font::new(&directory, "right").into(),
+            languages: BTreeMap::default(),
+            fallbacks: vec![],
+            title: None,
+            marginalia: None,
+            running_heads: None,
             default: true,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::from_str;
+
+    use super::Fonts;
+
+    #[test]
+    fn test_fallback_name() {
+        for (index, name) in [
+            (0, "a"),
+            (25, "z"),
+            (26, "aa"),
+            (27, "ab"),
+            (701, "zz"),
+            (702, "aaa"),
+        ] {
+            assert_eq!(
+                Fonts::get_fallback_name(index),
+                format!("fallbackfont{}", name)
+            );
+        }
+    }
+
+    #[test]
+    fn test_column_font() {
+        let fonts = from_str::<Fonts>(
+            r#"{
+    "left": {"family": "A", "regular": "Regular", "italic": null, "bold": null, "bold_italic": null, "metrics": {"size": 9.0, "skip": 11.0}},
+    "center": {"family": "B", "regular": "Regular", "italic": null, "bold": null, "bold_italic": null},
+    "right": {"family": "C", "regular": "Regular", "italic": null, "bold": null, "bold_italic": null, "fallbacks": [{"family": "D", "regular": "Regular", "italic": null, "bold": null, "bold_italic": null}]}
+}"#,
+        )
+        .unwrap();
+        assert_eq!(fonts.left.metrics.unwrap().size, 9.);
+        assert!(fonts.left.fallbacks.is_empty());
+        assert!(fonts.center.metrics.is_none());
+        assert_eq!(fonts.right.fallbacks.len(), 1);
+    }
+}
@@ -0,0 +1,205 @@
+use std::{collections::BTreeMap, fmt};
+
+use crate::{
+    span::{language::Language, position::Position as WordPosition, style::Style, Span, NBSP},
+    table::position::Position,
+};
+
+use super::{cosmic_font::CosmicFont, fallback::CosmicFallback};
+
+/// Characters that are never typeset with the column's font.
+/// `~`, `<`, and `>` are replaced with TeX commands.
+const IGNORED: [char; 4] = ['~', '<', '>', '\u{00AD}'];
+
+/// A character in a word, keyed by whether the word is in the body or a margin note and by the face (bold and italic flags) of the word's style.
+/// Each face of each font is checked separately, so a character might be set in a fallback font in one face but not in another.
+pub(crate) type FaceChar = (WordPosition, Style, char);
+
+/// A character that neither a column's font nor any of the fallback fonts can typeset.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MissingGlyph {
+    /// The column that the character is in.
+    pub column: Position,
+    /// The index of the word in the column.
+    pub word_index: usize,
+    /// The word that the character is in.
+    pub word: String,
+    /// The missing character.
+    pub character: char,
+}
+
+impl fmt::Display for MissingGlyph {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?} column, word {} \"{}\": '{}' (U+{:04X})",
+            self.column, self.word_index, self.word, self.character, self.character as u32
+        )
+    }
+}
+
+/// The result of checking a column's glyph coverage.
+#[derive(Default)]
+pub(crate) struct GlyphCoverage {
+    /// Characters that can't be typeset.
+    pub missing: Vec<MissingGlyph>,
+    /// Characters that are set in a fallback font, and the index of the fallback font.
+    pub fallbacks: BTreeMap<FaceChar, usize>,
+}
+
+impl GlyphCoverage {
+    /// Check the column's font and the fallback fonts for a glyph for every character in `span`.
+    ///
+    /// A character in the ranges of a fallback font that has it is set in that fallback font.
+    /// Otherwise, if the column's font doesn't have the character, it is set in the first fallback font without ranges that has it.
+    /// Each character is checked against the face of the word's style (regular, italic, bold, or bold italic).
+    /// If `marginalia` isn't None, it is the marginalia font and its style, and words in margin notes are checked against it instead of the column's font.
+    /// Raw TeX and words in `languages` (which have their own fonts) are ignored.
+    pub(crate) fn new(
+        span: &Span,
+        column: Position,
+        font: &CosmicFont,
+        marginalia: Option<(&CosmicFont, Style)>,
+        fallbacks: &[CosmicFallback],
+        languages: &[Language],
+    ) -> Self {
+        let mut coverage = Self::default();
+        // Cache whether each character can be typeset per face.
+        let mut checked = BTreeMap::new();
+        for (word_index, word) in span.0.iter().enumerate() {
+            if word.raw_tex.is_some()
+                || word
+                    .language
+                    .is_some_and(|language| languages.contains(&language))
+            {
+                continue;
+            }
+            // The bold and italic flags select the face.
+            let face = word.style.face();
+            // Margin notes are set in the marginalia font, in the marginalia font's style.
+            let (font, style) = match (word.position, marginalia) {
+                (WordPosition::Margin, Some((marginalia, marginalia_style))) => {
+                    (marginalia, face.with(marginalia_style))
+                }
+                _ => (font, face),
+            };
+            for character in word
+                .word
+                .chars()
+                .filter(|c| !c.is_whitespace() && *c != NBSP && !IGNORED.contains(c))
+            {
+                let key = (word.position, face, character);
+                let covered = *checked.entry(key).or_insert_with(|| {
+                    let has_glyph =
+                        |fallback: &CosmicFallback| fallback.font.has_glyph(style, character);
+                    // Characters in a fallback font's ranges are always set in that font.
+                    let index = match fallbacks
+                        .iter()
+                        .position(|fallback| fallback.is_forced(character) && has_glyph(fallback))
+                    {
+                        Some(index) => Some(index),
+                        None if font.has_glyph(style, character) => return true,
+                        None => fallbacks
+                            .iter()
+                            .position(|fallback| fallback.ranges.is_empty() && has_glyph(fallback)),
+                    };
+                    match index {
+                        Some(index) => {
+                            coverage.fallbacks.insert(key, index);
+                            true
+                        }
+                        None => false,
+                    }
+                });
+                if !covered {
+                    coverage.missing.push(MissingGlyph {
+                        column,
+                        word_index,
+                        word: word.word.clone(),
+                        character,
+                    });
+                }
+            }
+        }
+        coverage
+    }
+}
+
+#[cfg(all(test, feature = "default-fonts"))]
+mod tests {
+    use crate::{
+        font::{
+            cosmic_font::CosmicFont,
+            fallback::{CharRange, CosmicFallback, Script},
+        },
+        span::{language::Language, position::Position as WordPosition, style::Style, Span},
+        table::position::Position,
+    };
+
+    use super::GlyphCoverage;
+
+    #[test]
+    fn test_glyph_coverage() {
+        let span =
+            Span::from_md("Shalom שלום *and* <span lang=\"he\">שלום</span> αβ", false).unwrap();
+        let font = CosmicFont::default_left();
+        let coverage = GlyphCoverage::new(&span, Position::Left, &font, None, &[], &[]);
+        // Every Hebrew and Greek letter is missing, including the tagged word.
+        assert_eq!(coverage.missing.len(), 10);
+        assert_eq!(coverage.missing[0].word_index, 1);
+        assert_eq!(coverage.missing[0].character, 'ש');
+        assert!(coverage.fallbacks.is_empty());
+        // The tagged word has its own font.
+        let coverage =
+            GlyphCoverage::new(&span, Position::Left, &font, None, &[], &[Language::Hebrew]);
+        assert_eq!(coverage.missing.len(), 6);
+        // The fallback font doesn't have Hebrew either, but it does have Greek.
+        let mut fallbacks = [CosmicFallback {
+            font: CosmicFont::default_center(),
+            ranges: vec![],
+        }];
+        let coverage = GlyphCoverage::new(&span, Position::Left, &font, None, &fallbacks, &[]);
+        assert_eq!(coverage.missing.len(), 8);
+        assert_eq!(coverage.fallbacks.len(), 2);
+        assert_eq!(
+            coverage.fallbacks[&(WordPosition::Body, Style::REGULAR, 'α')],
+            0
+        );
+        // Latin characters are always set in the fallback font, even though the column's font has them.
+        fallbacks[0].ranges = vec![CharRange::Script(Script::Latin)];
+        let coverage = GlyphCoverage::new(&span, Position::Left, &font, None, &fallbacks, &[]);
+        assert_eq!(coverage.missing.len(), 10);
+        assert_eq!(
+            coverage.fallbacks[&(WordPosition::Body, Style::REGULAR, 'S')],
+            0
+        );
+        // Each face is checked separately.
+        assert_eq!(
+            coverage.fallbacks[&(WordPosition::Body, Style::ITALIC, 'd')],
+            0
+        );
+        assert!(!coverage
+            .fallbacks
+            .contains_key(&(WordPosition::Body, Style::REGULAR, 'd')));
+        assert!(!coverage
+            .fallbacks
+            .contains_key(&(WordPosition::Body, Style::REGULAR, 'α')));
+
+        // Margin notes are checked against the marginalia font, which has Greek.
+        let span = Span::from_md("αβ `αβ`", false).unwrap();
+        let marginalia = CosmicFont::default_center();
+        let coverage = GlyphCoverage::new(
+            &span,
+            Position::Left,
+            &font,
+            Some((&marginalia, Style::REGULAR)),
+            &[],
+            &[],
+        );
+        assert_eq!(coverage.missing.len(), 2);
+        assert!(coverage
+            .missing
+            .iter()
+            .all(|missing| missing.word_index == 0));
+    }
+}
@@ -1,82 +1,453 @@
+pub mod column_font;
 pub mod cosmic_font;
 pub mod cosmic_fonts;
 #[cfg(feature = "default-fonts")]
 mod default_fonts;
 #[cfg(feature = "default-fonts")]
 pub mod default_tex_fonts;
+pub mod face;
+pub mod fallback;
+pub mod font_directory;
 pub mod font_metrics;
 pub mod font_paths;
 pub mod fonts;
+pub mod glyph_coverage;
+pub mod shared_font_system;
+pub mod styled_font;
+pub mod synthetic;
 pub mod tex_font;
 pub mod tex_fonts;
 
-use std::path::{Path, PathBuf};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
-use cosmic_text::FontSystem;
+use cosmic_text::fontdb::Source;
 use serde::{Deserialize, Serialize};
 
 use crate::{
     error::Error,
-    font::{cosmic_font::CosmicFont, font_paths::FontPaths, tex_font::TexFont},
+    font::{
+        cosmic_font::CosmicFont, face::Faces, font_directory::FontDirectory, font_paths::FontPaths,
+        shared_font_system::SharedFontSystem, synthetic::Synthetic, tex_font::TexFont,
+    },
     prelude::FontMetrics,
 };
 
 #[cfg(feature = "default-fonts")]
 const DEFAULT_ROOT_DIRECTORY: &str = "talmudifier_fonts";
 
-/// The paths to the font files.
-#[derive(Deserialize, Serialize)]
-pub struct Font {
-    /// The directory that the fonts are in.
-    pub directory: PathBuf,
-    /// The filename of the regular-style .ttf file.
-    pub regular: String,
-    /// The filename of the italicized .ttf file. If None, `regular` is used.
-    pub italic: Option<String>,
-    /// The filename of the bold .ttf file. If None, `italic` is used.
-    pub bold: Option<String>,
-    /// The filename of the bold italic .ttf file. If None, `bold` is used.
-    pub bold_italic: Option<String>,
+/// A column's font.
+///
+/// A font can be a directory of font files, a font family that is installed on the system, or font data in memory.
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum Font {
+    /// The paths to the font files.
+    Files {
+        /// The directory that the fonts are in.
+        directory: PathBuf,
+        /// The filename of the regular-style .ttf file.
+        regular: String,
+        /// The filename of the italicized .ttf file. If None, `regular` is used.
+        italic: Option<String>,
+        /// The filename of the bold .ttf file. If None, `italic` is used.
+        bold: Option<String>,
+        /// The filename of the bold italic .ttf file. If None, `bold` is used.
+        bold_italic: Option<String>,
+        /// The face of each style in files that have more than one face, e.g. .ttc collections.
+        #[serde(default, skip_serializing_if = "Faces::is_empty")]
+        faces: Faces,
+        /// OpenType features, e.g. `"onum"`. See [`TexFont::set_features`].
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        features: Vec<String>,
+        /// If true, missing styles are synthesized from the regular style instead of falling back to another style. See [`Synthetic`].
+        #[serde(default)]
+        synthesize: bool,
+    },
+    /// A font family that is installed on the system, e.g. `"Frank Ruehl CLM"`.
+    /// Cosmic finds the font in its font database (fontdb), and XeTeX finds the font by name.
+    Family {
+        /// The name of the font family.
+        family: String,
+        /// The name of the regular style, e.g. `"Regular"` or `"Medium"`.
+        regular: String,
+        /// The name of the italic style, e.g. `"Italic"`. If None, `regular` is used.
+        italic: Option<String>,
+        /// The name of the bold style, e.g. `"Bold"`. If None, `italic` is used.
+        bold: Option<String>,
+        /// The name of the bold italic style, e.g. `"Bold Italic"`. If None, `bold` is used.
+        bold_italic: Option<String>,
+        /// OpenType features, e.g. `"onum"`. See [`TexFont::set_features`].
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        features: Vec<String>,
+        /// If true, missing styles are synthesized from the regular style instead of falling back to another style. See [`Synthetic`].
+        #[serde(default)]
+        synthesize: bool,
+    },
+    /// Font data that is stored in memory, e.g. via `include_bytes!`.
+    /// This can't be serialized or deserialized.
+    #[serde(skip)]
+    Bytes {
+        /// The regular-style font data.
+        regular: Vec<u8>,
+        /// The italicized font data. If None, `regular` is used.
+        italic: Option<Vec<u8>>,
+        /// The bold font data. If None, `italic` is used.
+        bold: Option<Vec<u8>>,
+        /// The bold italic font data. If None, `bold` is used.
+        bold_italic: Option<Vec<u8>>,
+        /// The face of each style in data that has more than one face, e.g. .ttc collections.
+        faces: Faces,
+        /// OpenType features, e.g. `"onum"`. See [`TexFont::set_features`].
+        features: Vec<String>,
+        /// If true, missing styles are synthesized from the regular style instead of falling back to another style. See [`Synthetic`].
+        synthesize: bool,
+    },
 }
 
 impl Font {
     #[cfg(feature = "default-fonts")]
     pub(super) fn new(fonts_directory: &Path, folder: &str) -> Self {
-        Self {
+        Self::Files {
             directory: fonts_directory.join(folder),
             regular: "regular.ttf".to_string(),
             italic: Some("italic.ttf".to_string()),
             bold: Some("bold.ttf".to_string()),
             bold_italic: Some("bold_italic.ttf".to_string()),
+            faces: Faces::default(),
+            features: vec![],
+            synthesize: false,
         }
     }
 
-    /// Create a `CosmicFont` from the font files.
+    /// Create a `CosmicFont` from the font files or family.
     pub(super) fn to_cosmic(&self, metrics: &FontMetrics) -> Result<CosmicFont, Error> {
-        let font_paths = self.font_paths()?;
-        match CosmicFont::new(&font_paths, metrics, FontSystem::new()) {
-            Ok(c) => Ok(c),
-            Err(error) => Err(Error::CosmicFont(error)),
+        if let Some((font, styles)) = self.get_synthetic() {
+            let mut cosmic_font = font.to_cosmic(metrics)?;
+            cosmic_font.set_synthetic(&styles);
+            return Ok(cosmic_font);
+        }
+        let mut cosmic_font = match self {
+            Self::Files {
+                directory,
+                regular,
+                italic,
+                bold,
+                bold_italic,
+                faces,
+                ..
+            } => {
+                let font_paths = Self::font_paths(directory, regular, italic, bold, bold_italic)?;
+                CosmicFont::new(&font_paths, faces, metrics, SharedFontSystem::default())
+            }
+            Self::Family {
+                family,
+                regular,
+                italic,
+                bold,
+                bold_italic,
+                ..
+            } => {
+                let italic = italic.as_ref().unwrap_or(regular);
+                let bold = bold.as_ref().unwrap_or(italic);
+                let bold_italic = bold_italic.as_ref().unwrap_or(bold);
+                CosmicFont::new_from_family(
+                    family,
+                    [regular, italic, bold, bold_italic],
+                    metrics,
+                    SharedFontSystem::default(),
+                )
+            }
+            Self::Bytes {
+                regular,
+                italic,
+                bold,
+                bold_italic,
+                faces,
+                ..
+            } => {
+                let [regular, italic, bold, bold_italic] =
+                    Self::get_bytes(regular, italic, bold, bold_italic);
+                CosmicFont::new_from_bytes(
+                    regular.to_vec(),
+                    italic.to_vec(),
+                    bold.to_vec(),
+                    bold_italic.to_vec(),
+                    faces,
+                    metrics,
+                    SharedFontSystem::default(),
+                )
+            }
+        }?;
+        (cosmic_font.features, cosmic_font.variations) =
+            CosmicFont::parse_features(self.features())?;
+        Ok(cosmic_font)
+    }
+
+    /// Create a `TexFont` from the font files or family.
+    ///
+    /// If the font is in memory, it's written to a unique temporary directory.
+    pub(super) fn to_tex(&self, name: &str) -> Result<TexFont, Error> {
+        if let Some((font, styles)) = self.get_synthetic() {
+            let mut tex_font = font.to_tex(name)?;
+            tex_font.set_synthetic(&styles);
+            return Ok(tex_font);
+        }
+        let mut tex_font = match self {
+            Self::Files {
+                directory,
+                regular,
+                italic,
+                bold,
+                bold_italic,
+                faces,
+                ..
+            } => {
+                if faces.is_empty() {
+                    TexFont::new(name, directory, regular, italic, bold, bold_italic)
+                } else {
+                    // Declare every style so that each one can have a face index.
+                    let paths = Self::font_paths(directory, regular, italic, bold, bold_italic)?;
+                    let files = [
+                        &paths.regular,
+                        &paths.italic,
+                        &paths.bold,
+                        &paths.bold_italic,
+                    ]
+                    .map(|path| path.file_name().unwrap().to_string_lossy().to_string());
+                    let mut tex_font = TexFont::new(
+                        name,
+                        directory,
+                        &files[0],
+                        &Some(files[1].clone()),
+                        &Some(files[2].clone()),
+                        &Some(files[3].clone()),
+                    );
+                    tex_font.set_face_indices(Self::get_face_indices(
+                        faces,
+                        [
+                            &paths.regular,
+                            &paths.italic,
+                            &paths.bold,
+                            &paths.bold_italic,
+                        ]
+                        .map(|path| Source::File(path.clone())),
+                    )?);
+                    tex_font
+                }
+            }
+            Self::Family {
+                family,
+                regular,
+                italic,
+                bold,
+                bold_italic,
+                ..
+            } => TexFont::new_from_family(name, family, regular, italic, bold, bold_italic),
+            Self::Bytes {
+                regular,
+                italic,
+                bold,
+                bold_italic,
+                faces,
+                ..
+            } => {
+                let bytes = Self::get_bytes(regular, italic, bold, bold_italic);
+                let directory = FontDirectory::new().map_err(Error::TexFonts)?;
+                let path = directory
+                    .write_fonts(name, bytes)
+                    .map_err(Error::TexFonts)?;
+                let [regular, italic, bold, bold_italic] =
+                    FontDirectory::FILENAMES.map(|f| f.to_string());
+                let mut tex_font = TexFont::new(
+                    name,
+                    path,
+                    &regular,
+                    &Some(italic),
+                    &Some(bold),
+                    &Some(bold_italic),
+                );
+                if !faces.is_empty() {
+                    tex_font.set_face_indices(Self::get_face_indices(
+                        faces,
+                        bytes.map(|b| Source::Binary(Arc::new(b.to_vec()))),
+                    )?);
+                }
+                tex_font._directory = Some(directory);
+                tex_font
+            }
+        };
+        // Reject features that Cosmic and XeTeX can't both apply.
+        CosmicFont::parse_features(self.features())?;
+        tex_font.set_features(self.features());
+        Ok(tex_font)
+    }
+
+    /// If missing styles should be synthesized, returns:
+    ///
+    /// - A copy of this font that declares every style, using the style that each missing style is synthesized from.
+    /// - How each style is synthesized.
+    ///
+    /// Returns None if missing styles shouldn't be synthesized.
+    fn get_synthetic(&self) -> Option<(Self, [Synthetic; 4])> {
+        // Declare every style using the style that it's synthesized from.
+        fn declare<T: Clone>(
+            styles: &[Synthetic; 4],
+            regular: &T,
+            italic: &Option<T>,
+            bold: &Option<T>,
+            bold_italic: &Option<T>,
+        ) -> [T; 4] {
+            let values = [
+                Some(regular),
+                italic.as_ref(),
+                bold.as_ref(),
+                bold_italic.as_ref(),
+            ];
+            styles.map(|style| values[style.base].unwrap().clone())
+        }
+        let get_faces = |styles: &[Synthetic; 4], faces: &Faces| {
+            let [regular, italic, bold, bold_italic] =
+                styles.map(|style| faces.get()[style.base].cloned());
+            Faces {
+                regular,
+                italic,
+                bold,
+                bold_italic,
+            }
+        };
+
+        match self {
+            Self::Files {
+                directory,
+                regular,
+                italic,
+                bold,
+                bold_italic,
+                faces,
+                features,
+                synthesize: true,
+            } => {
+                let styles =
+                    Synthetic::get_styles(italic.is_some(), bold.is_some(), bold_italic.is_some());
+                let [regular, italic, bold, bold_italic] =
+                    declare(&styles, regular, italic, bold, bold_italic);
+                Some((
+                    Self::Files {
+                        directory: directory.clone(),
+                        regular,
+                        italic: Some(italic),
+                        bold: Some(bold),
+                        bold_italic: Some(bold_italic),
+                        faces: get_faces(&styles, faces),
+                        features: features.clone(),
+                        synthesize: false,
+                    },
+                    styles,
+                ))
+            }
+            Self::Family {
+                family,
+                regular,
+                italic,
+                bold,
+                bold_italic,
+                features,
+                synthesize: true,
+            } => {
+                let styles =
+                    Synthetic::get_styles(italic.is_some(), bold.is_some(), bold_italic.is_some());
+                let [regular, italic, bold, bold_italic] =
+                    declare(&styles, regular, italic, bold, bold_italic);
+                Some((
+                    Self::Family {
+                        family: family.clone(),
+                        regular,
+                        italic: Some(italic),
+                        bold: Some(bold),
+                        bold_italic: Some(bold_italic),
+                        features: features.clone(),
+                        synthesize: false,
+                    },
+                    styles,
+                ))
+            }
+            Self::Bytes {
+                regular,
+                italic,
+                bold,
+                bold_italic,
+                faces,
+                features,
+                synthesize: true,
+            } => {
+                let styles =
+                    Synthetic::get_styles(italic.is_some(), bold.is_some(), bold_italic.is_some());
+                let [regular, italic, bold, bold_italic] =
+                    declare(&styles, regular, italic, bold, bold_italic);
+                Some((
+                    Self::Bytes {
+                        regular,
+                        italic: Some(italic),
+                        bold: Some(bold),
+                        bold_italic: Some(bold_italic),
+                        faces: get_faces(&styles, faces),
+                        features: features.clone(),
+                        synthesize: false,
+                    },
+                    styles,
+                ))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the face index of each style. `sources` are the regular, italic, bold, and bold italic font sources.
+    fn get_face_indices(faces: &Faces, sources: [Source; 4]) -> Result<[u32; 4], Error> {
+        let mut indices = [0; 4];
+        for ((index, face), source) in indices.iter_mut().zip(faces.get()).zip(sources) {
+            if let Some(face) = face {
+                *index = face.get_index(source)?;
+            }
+        }
+        Ok(indices)
+    }
+
+    /// Returns the OpenType features.
+    fn features(&self) -> &[String] {
+        match self {
+            Self::Files { features, .. }
+            | Self::Family { features, .. }
+            | Self::Bytes { features, .. } => features,
         }
     }
 
-    /// Create a `TexFont` from the font files.
-    pub(super) fn to_tex(&self, name: &str) -> TexFont {
-        TexFont::new(
-            name,
-            &self.directory,
-            &self.regular,
-            &self.italic,
-            &self.bold,
-            &self.bold_italic,
-        )
+    /// Returns the regular, italic, bold, and bold italic font data, with fallbacks.
+    fn get_bytes<'a>(
+        regular: &'a [u8],
+        italic: &'a Option<Vec<u8>>,
+        bold: &'a Option<Vec<u8>>,
+        bold_italic: &'a Option<Vec<u8>>,
+    ) -> [&'a [u8]; 4] {
+        let italic = italic.as_deref().unwrap_or(regular);
+        let bold = bold.as_deref().unwrap_or(italic);
+        let bold_italic = bold_italic.as_deref().unwrap_or(bold);
+        [regular, italic, bold, bold_italic]
     }
 
-    fn font_paths(&self) -> Result<FontPaths, Error> {
-        let regular = self.get_font(&self.regular)?;
-        let italic = self.get_optional_font(&self.italic, &regular)?;
-        let bold = self.get_optional_font(&self.bold, &italic)?;
-        let bold_italic = self.get_optional_font(&self.bold_italic, &bold)?;
+    fn font_paths(
+        directory: &Path,
+        regular: &str,
+        italic: &Option<String>,
+        bold: &Option<String>,
+        bold_italic: &Option<String>,
+    ) -> Result<FontPaths, Error> {
+        let regular = Self::get_font(directory, regular)?;
+        let italic = Self::get_optional_font(directory, italic, &regular)?;
+        let bold = Self::get_optional_font(directory, bold, &italic)?;
+        let bold_italic = Self::get_optional_font(directory, bold_italic, &bold)?;
         Ok(FontPaths {
             regular,
             italic,
@@ -85,18 +456,22 @@ impl Font {
         })
     }
 
-    fn get_font(&self, path: &str) -> Result<PathBuf, Error> {
-        let path = self.directory.join(path);
+    fn get_font(directory: &Path, path: &str) -> Result<PathBuf, Error> {
+        let path = directory.join(path);
         if path.exists() {
             Ok(path)
         } else {
-            Err(Error::NoFont(path))
+            Err(Error::NoFont(path.to_string_lossy().to_string()))
         }
     }
 
-    fn get_optional_font(&self, path: &Option<String>, fallback: &Path) -> Result<PathBuf, Error> {
+    fn get_optional_font(
+        directory: &Path,
+        path: &Option<String>,
+        fallback: &Path,
+    ) -> Result<PathBuf, Error> {
         match path {
-            Some(path) => self.get_font(path),
+            Some(path) => Self::get_font(directory, path),
             None => Ok(fallback.to_path_buf()),
         }
     }
@@ -0,0 +1,162 @@
+use std::{
+    collections::{HashMap, HashSet},
+    hash::{DefaultHasher, Hash, Hasher},
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use cosmic_text::{
+    fontdb::{Language, Source, ID},
+    Attrs, AttrsOwned, Family, FontSystem,
+};
+use lazy_static::lazy_static;
+
+lazy_static! {
+    /// The font system that is shared by every font in the process.
+    static ref FONT_SYSTEM: SharedFontSystem = SharedFontSystem::new();
+}
+
+/// A Cosmic font system that is shared by every column's font, and by every `Talmudifier` in the process.
+///
+/// Creating a `FontSystem` scans all of the system fonts, which is slow and uses a lot of memory, so it should only be done once.
+/// `SharedFontSystem::default()` returns the process-wide font system.
+#[derive(Clone)]
+pub struct SharedFontSystem(Arc<Mutex<FontDatabase>>);
+
+impl SharedFontSystem {
+    /// Create a new font system that isn't shared with the process-wide font system.
+    pub fn new() -> Self {
+        Self(Arc::new(Mutex::new(FontDatabase {
+            font_system: FontSystem::new(),
+            loaded: HashMap::default(),
+            aliased: HashSet::default(),
+        })))
+    }
+
+    /// Lock the font system.
+    pub fn lock(&self) -> MutexGuard<'_, FontDatabase> {
+        self.0.lock().unwrap()
+    }
+}
+
+impl Default for SharedFontSystem {
+    fn default() -> Self {
+        FONT_SYSTEM.clone()
+    }
+}
+
+/// A font system and the font data that has been loaded into it.
+pub struct FontDatabase {
+    pub font_system: FontSystem,
+    /// The IDs of the faces of font data that has already been loaded, keyed by a hash of the data.
+    loaded: HashMap<u64, Vec<ID>>,
+    /// The IDs of the faces that have been added again with a unique family name.
+    aliased: HashSet<ID>,
+}
+
+impl FontDatabase {
+    /// Load font data and return the IDs of its faces.
+    /// If the same data has already been loaded, it isn't loaded again.
+    pub fn load(&mut self, data: Vec<u8>) -> Vec<ID> {
+        let mut hasher = DefaultHasher::new();
+        data.hash(&mut hasher);
+        let font_system = &mut self.font_system;
+        self.loaded
+            .entry(hasher.finish())
+            .or_insert_with(|| {
+                font_system
+                    .db_mut()
+                    .load_font_source(Source::Binary(Arc::new(data)))
+                    .to_vec()
+            })
+            .clone()
+    }
+
+    /// Returns attributes that only match the face `id`.
+    ///
+    /// Faces can have the same family, style, weight, and stretch, e.g. the faces of a collection, so Cosmic can't always select a face by its attributes.
+    /// Instead, the face is added to the database again with a family name that is unique to it.
+    pub fn get_attrs(&mut self, id: ID) -> AttrsOwned {
+        let family = format!("talmudifier-face-{}", id);
+        let mut face = self.font_system.db().face(id).unwrap().clone();
+        if self.aliased.insert(id) {
+            face.id = ID::dummy();
+            face.families = vec![(family.clone(), Language::English_UnitedStates)];
+            self.font_system.db_mut().push_face_info(face.clone());
+        }
+        AttrsOwned::new(
+            Attrs::new()
+                .family(Family::Name(&family))
+                .style(face.style)
+                .weight(face.weight)
+                .stretch(face.stretch),
+        )
+    }
+}
+
+#[cfg(all(test, feature = "default-fonts"))]
+mod tests {
+    use cosmic_text::fontdb::{Query, ID};
+
+    use crate::font::default_fonts::{AVERIA_REGULAR, EB_GARAMOND_BOLD, EB_GARAMOND_REGULAR};
+
+    use super::SharedFontSystem;
+
+    #[test]
+    fn test_shared_font_system() {
+        let font_system = SharedFontSystem::new();
+        let shared = font_system.clone();
+        let mut database = font_system.lock();
+        let num_faces = database.font_system.db().len();
+        let ids = database.load(EB_GARAMOND_REGULAR.to_vec());
+        assert_eq!(ids.len(), 1);
+        assert_eq!(database.font_system.db().len(), num_faces + 1);
+        // The same data isn't loaded twice.
+        assert_eq!(database.load(EB_GARAMOND_REGULAR.to_vec()), ids);
+        assert_eq!(database.font_system.db().len(), num_faces + 1);
+        assert_ne!(database.load(EB_GARAMOND_BOLD.to_vec()), ids);
+        drop(database);
+        // Clones share the same font system.
+        assert_eq!(shared.lock().font_system.db().len(), num_faces + 2);
+    }
+
+    #[test]
+    fn test_face_attrs() {
+        let font_system = SharedFontSystem::new();
+        let mut database = font_system.lock();
+        let garamond = database.load(EB_GARAMOND_REGULAR.to_vec())[0];
+        // Give another face the same family, style, weight, and stretch.
+        let averia = database.load(AVERIA_REGULAR.to_vec())[0];
+        let mut face = database.font_system.db().face(averia).unwrap().clone();
+        let garamond_face = database.font_system.db().face(garamond).unwrap().clone();
+        face.id = ID::dummy();
+        face.families = garamond_face.families.clone();
+        face.style = garamond_face.style;
+        face.weight = garamond_face.weight;
+        face.stretch = garamond_face.stretch;
+        database.font_system.db_mut().push_face_info(face);
+        // Each face's attributes select that face.
+        for id in [garamond, averia] {
+            let attrs = database.get_attrs(id);
+            let attrs = attrs.as_attrs();
+            let selected = database
+                .font_system
+                .db()
+                .query(&Query {
+                    families: &[attrs.family],
+                    weight: attrs.weight,
+                    stretch: attrs.stretch,
+                    style: attrs.style,
+                })
+                .unwrap();
+            assert_eq!(
+                database
+                    .font_system
+                    .db()
+                    .face(selected)
+                    .unwrap()
+                    .post_script_name,
+                database.font_system.db().face(id).unwrap().post_script_name
+            );
+        }
+    }
+}
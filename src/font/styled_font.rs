@@ -0,0 +1,108 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{error::Error, span::style::Style};
+
+use super::{cosmic_font::CosmicFont, font_metrics::FontMetrics, tex_font::TexFont, Font};
+
+/// The style that a [`StyledFont`] is set in.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, Eq, PartialEq)]
+pub enum FontStyle {
+    #[default]
+    Regular,
+    Italic,
+    Bold,
+    BoldItalic,
+}
+
+impl FontStyle {
+    /// Returns the TeX command that switches to this style.
+    const fn get_command(&self) -> &'static str {
+        match self {
+            Self::Regular => "",
+            Self::Italic => "\\itshape",
+            Self::Bold => "\\bfseries",
+            Self::BoldItalic => "\\bfseries\\itshape",
+        }
+    }
+
+    /// Returns the bold and italic flags of this style.
+    pub(crate) const fn get_style(&self) -> Style {
+        match self {
+            Self::Regular => Style::REGULAR,
+            Self::Italic => Style::ITALIC,
+            Self::Bold => Style::BOLD,
+            Self::BoldItalic => Style::BOLD.with(Style::ITALIC),
+        }
+    }
+}
+
+/// A font with its own size and style that is used for something other than a column, e.g. the title.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct StyledFont {
+    /// The font files or family.
+    pub font: Font,
+    /// The font size and line skip.
+    pub metrics: FontMetrics,
+    /// The style that the text is set in.
+    #[serde(default)]
+    pub style: FontStyle,
+}
+
+impl StyledFont {
+    pub fn new(font: Font, metrics: FontMetrics, style: FontStyle) -> Self {
+        Self {
+            font,
+            metrics,
+            style,
+        }
+    }
+
+    /// Convert to a TexFont. The command sets the font, size, and style.
+    pub(super) fn to_tex(&self, name: &str) -> Result<TexFont, Error> {
+        let mut tex_font = self.font.to_tex(name)?;
+        tex_font.set_metrics(&self.metrics);
+        tex_font.command.push_str(self.style.get_command());
+        Ok(tex_font)
+    }
+
+    /// Convert to a CosmicFont.
+    pub(super) fn to_cosmic(&self) -> Result<CosmicFont, Error> {
+        self.font.to_cosmic(&self.metrics)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::font::{font_metrics::FontMetrics, Font};
+
+    use super::{FontStyle, StyledFont};
+
+    #[test]
+    fn test_styled_font() {
+        let font = Font::Family {
+            family: "EB Garamond".to_string(),
+            regular: "Regular".to_string(),
+            italic: None,
+            bold: Some("Bold".to_string()),
+            bold_italic: None,
+            features: vec![],
+            synthesize: false,
+        };
+        let styled_font = StyledFont::new(
+            font,
+            FontMetrics {
+                size: 24.,
+                skip: 28.,
+            },
+            FontStyle::Bold,
+        );
+        let tex_font = styled_font.to_tex("titlefont").unwrap();
+        assert!(tex_font
+            .font_family()
+            .starts_with("\\newfontfamily\\titlefont["));
+        assert_eq!(
+            tex_font.command,
+            "\\titlefont\\fontsize{24}{28}\\selectfont\\bfseries"
+        );
+    }
+}
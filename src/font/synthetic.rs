@@ -0,0 +1,131 @@
+/// The fontspec `FakeSlant` factor. This matches Cosmic's synthetic italic, which skews glyphs by 14 degrees.
+const FAKE_SLANT: f32 = 0.25;
+/// The fontspec `FakeBold` factor.
+const FAKE_BOLD: f32 = 1.5;
+
+/// How a missing style is synthesized from another style.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Synthetic {
+    /// The index of the style that this style is synthesized from: regular, italic, bold, or bold italic.
+    pub base: usize,
+    /// If true, the base style is slanted.
+    pub slant: bool,
+    /// If true, the base style is emboldened.
+    pub bold: bool,
+}
+
+impl Synthetic {
+    /// Given whether the regular, italic, bold, and bold italic styles exist, returns how each style is synthesized.
+    ///
+    /// - A missing italic style is a slanted regular style.
+    /// - A missing bold style is an emboldened regular style.
+    /// - A missing bold italic style is a slanted bold style, an emboldened italic style, or an emboldened and slanted regular style.
+    pub fn get_styles(italic: bool, bold: bool, bold_italic: bool) -> [Self; 4] {
+        let style = |base: usize, slant: bool, bold: bool| Self { base, slant, bold };
+        [
+            style(0, false, false),
+            if italic {
+                style(1, false, false)
+            } else {
+                style(0, true, false)
+            },
+            if bold {
+                style(2, false, false)
+            } else {
+                style(0, false, true)
+            },
+            if bold_italic {
+                style(3, false, false)
+            } else if bold {
+                style(2, true, false)
+            } else if italic {
+                style(1, false, true)
+            } else {
+                style(0, true, true)
+            },
+        ]
+    }
+
+    /// Returns the fontspec features that synthesize this style, if any.
+    pub fn get_tex_features(&self) -> Option<String> {
+        match (self.slant, self.bold) {
+            (false, false) => None,
+            (true, false) => Some(format!("FakeSlant={}", FAKE_SLANT)),
+            (false, true) => Some(format!("FakeBold={}", FAKE_BOLD)),
+            (true, true) => Some(format!("FakeSlant={}, FakeBold={}", FAKE_SLANT, FAKE_BOLD)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "default-fonts")]
+    use crate::{page::Page, xetex::get_num_lines};
+
+    use crate::font::Font;
+
+    use super::Synthetic;
+    #[cfg(feature = "default-fonts")]
+    use super::FAKE_BOLD;
+
+    #[test]
+    fn test_synthetic() {
+        // IM Fell has an italic style but no bold style.
+        let styles = Synthetic::get_styles(true, false, false);
+        assert_eq!(styles[1].get_tex_features(), None);
+        assert_eq!(styles[2].base, 0);
+        assert_eq!(styles[2].get_tex_features().unwrap(), "FakeBold=1.5");
+        assert_eq!(styles[3].base, 1);
+        assert!(styles[3].bold && !styles[3].slant);
+        // Only a regular style.
+        let styles = Synthetic::get_styles(false, false, false);
+        assert!(styles.iter().all(|style| style.base == 0));
+        assert_eq!(
+            styles[3].get_tex_features().unwrap(),
+            "FakeSlant=0.25, FakeBold=1.5"
+        );
+    }
+
+    #[test]
+    fn test_synthesize() {
+        let font = Font::Family {
+            family: "Frank Ruehl CLM".to_string(),
+            regular: "Medium".to_string(),
+            italic: None,
+            bold: Some("Bold".to_string()),
+            bold_italic: None,
+            features: vec![],
+            synthesize: true,
+        };
+        let tex_font = font.to_tex("frank").unwrap();
+        // The missing styles are declared with the styles that they're synthesized from.
+        assert!(tex_font
+            .font_family()
+            .contains("ItalicFeatures={FakeSlant=0.25}, BoldItalicFeatures={FakeSlant=0.25}"));
+        assert!(tex_font
+            .font_family()
+            .contains("ItalicFont={* Medium}, BoldFont={* Bold}, BoldItalicFont={* Bold}"));
+    }
+
+    #[test]
+    #[cfg(feature = "default-fonts")]
+    fn test_fake_bold() {
+        // Cosmic measures an emboldened style with the widths of its base style,
+        // so XeTeX must break a paragraph into the same lines with and without `FakeBold`.
+        let page = Page::default();
+        let lorem = include_str!("../../test_text/lorem.txt");
+        let num_lines = |features: String| {
+            let tex = format!(
+                "{}\\leftfont\\addfontfeatures{{{}}} {}\\par{}",
+                page.preamble.as_ref().unwrap(),
+                features,
+                lorem,
+                Page::END_DOCUMENT
+            );
+            get_num_lines(tex).unwrap().into_iter().sum::<usize>()
+        };
+        let regular = num_lines(String::new());
+        assert!(regular > 1);
+        assert_eq!(num_lines(format!("FakeBold={}", FAKE_BOLD)), regular);
+    }
+}
@@ -1,13 +1,33 @@
 use std::path::Path;
 
+use crate::tex;
+
+use super::{
+    cosmic_font::CosmicFont, font_directory::FontDirectory, font_metrics::FontMetrics,
+    synthetic::Synthetic,
+};
+
 pub struct TexFont {
-    /// The font family declaration.
-    pub font_family: String,
     /// The command used to set the text to the target font, style, and size.
     pub command: String,
+    /// The name of the font family command, without the backslash.
+    name: String,
+    /// The regular font file, or the name of an installed font family.
+    font: String,
+    /// The options of the font family, e.g. `Path=fonts/`.
+    options: Vec<String>,
+    /// The features of the regular, italic, bold, and bold italic styles, e.g. `FakeSlant=0.25`.
+    style_features: [Vec<String>; 4],
+    /// The fonts of each style, e.g. `ItalicFont=italic.ttf`.
+    style_fonts: Vec<String>,
+    /// If the font was written from memory, this is the temporary directory that it was written to.
+    /// The directory is deleted when the `TexFont` is dropped.
+    pub(crate) _directory: Option<FontDirectory>,
 }
 
 impl TexFont {
+    const LIGATURES: &str = "Ligatures=TeX";
+
     pub fn new<P: AsRef<Path>>(
         name: &str,
         path: P,
@@ -18,33 +38,214 @@ impl TexFont {
     ) -> Self {
         const STYLES: [&str; 3] = ["ItalicFont", "BoldFont", "BoldItalicFont"];
 
-        // The font family declaration.
-        let mut font_family = format!(
-            "\\newfontfamily\\{}[Path={}/, Ligatures=TeX",
-            &name,
-            &path.as_ref().to_str().unwrap().replace("\\", "/")
-        );
+        let options = vec![
+            format!(
+                "Path={}/",
+                &path.as_ref().to_str().unwrap().replace("\\", "/")
+            ),
+            Self::LIGATURES.to_string(),
+        ];
 
         // Try to add styles to the font declaration.
-        let styles = [italic, bold, bold_italic]
+        let style_fonts = [italic, bold, bold_italic]
             .iter()
             .zip(STYLES)
             .filter_map(|(f, s)| f.as_ref().map(|f| format!("{}={}", s, f)))
-            .collect::<Vec<String>>()
-            .join(", ");
-        if !styles.is_empty() {
-            font_family.push_str(", ");
-            font_family.push_str(&styles);
-        }
+            .collect::<Vec<String>>();
+
+        Self::new_from_options(name, regular, options, style_fonts)
+    }
 
-        // Add the regular style.
-        font_family.push_str(&format!("]{{{}}}", regular));
+    /// Declare a font family that is installed on the system.
+    /// XeTeX finds each style by its full name, e.g. `Frank Ruehl CLM Bold`.
+    ///
+    /// Every style is declared so that fontspec doesn't look for a style that Cosmic doesn't measure.
+    /// A missing italic style is the regular style, a missing bold style is the italic style, and a missing bold italic style is the bold style.
+    pub fn new_from_family(
+        name: &str,
+        family: &str,
+        regular: &str,
+        italic: &Option<String>,
+        bold: &Option<String>,
+        bold_italic: &Option<String>,
+    ) -> Self {
+        const STYLES: [&str; 4] = ["UprightFont", "ItalicFont", "BoldFont", "BoldItalicFont"];
 
-        // This is the font size plus the font command.
-        let command = format!("\\{}", name);
+        let italic = italic.as_deref().unwrap_or(regular);
+        let bold = bold.as_deref().unwrap_or(italic);
+        let bold_italic = bold_italic.as_deref().unwrap_or(bold);
+        let style_fonts = [regular, italic, bold, bold_italic]
+            .iter()
+            .zip(STYLES)
+            .map(|(f, s)| format!("{}={{* {}}}", s, f))
+            .collect::<Vec<String>>();
+        Self::new_from_options(name, family, vec![Self::LIGATURES.to_string()], style_fonts)
+    }
+
+    fn new_from_options(
+        name: &str,
+        font: &str,
+        options: Vec<String>,
+        style_fonts: Vec<String>,
+    ) -> Self {
         Self {
-            command,
-            font_family,
+            command: format!("\\{}", name),
+            name: name.to_string(),
+            font: font.to_string(),
+            options,
+            style_features: Default::default(),
+            style_fonts,
+            _directory: None,
         }
     }
+
+    /// Returns the font family declaration, e.g. `\newfontfamily\leftfont[Path=fonts/, Ligatures=TeX]{regular.ttf}`.
+    pub fn font_family(&self) -> String {
+        const STYLES: [&str; 4] = [
+            "UprightFeatures",
+            "ItalicFeatures",
+            "BoldFeatures",
+            "BoldItalicFeatures",
+        ];
+        let style_features = STYLES
+            .iter()
+            .zip(&self.style_features)
+            .filter(|(_, features)| !features.is_empty())
+            .map(|(style, features)| format!("{}={{{}}}", style, features.join(", ")));
+        let options = self
+            .options
+            .iter()
+            .cloned()
+            .chain(style_features)
+            .chain(self.style_fonts.iter().cloned())
+            .collect::<Vec<String>>();
+        format!(
+            "\\newfontfamily\\{}[{}]{{{}}}",
+            self.name,
+            options.join(", "),
+            self.font
+        )
+    }
+
+    /// Set the index of the face of each style in files that have more than one face, e.g. .ttc collections.
+    /// `indices` are the regular, italic, bold, and bold italic face indices.
+    pub fn set_face_indices(&mut self, indices: [u32; 4]) {
+        self.add_style_features(indices.map(|i| Some(format!("FontIndex={}", i))));
+    }
+
+    /// Synthesize missing styles with fontspec's `FakeSlant` and `FakeBold`.
+    /// `styles` are the regular, italic, bold, and bold italic styles.
+    pub(crate) fn set_synthetic(&mut self, styles: &[Synthetic; 4]) {
+        self.add_style_features(styles.map(|style| style.get_tex_features()));
+    }
+
+    /// Add features to the regular, italic, bold, and bold italic styles, e.g. `ItalicFeatures={FakeSlant=0.25}`.
+    fn add_style_features(&mut self, features: [Option<String>; 4]) {
+        for (style_features, features) in self.style_features.iter_mut().zip(features) {
+            style_features.extend(features);
+        }
+    }
+
+    /// Set the font size and line skip in the font command.
+    pub fn set_metrics(&mut self, metrics: &FontMetrics) {
+        self.command
+            .push_str(&tex!("fontsize", metrics.size, metrics.skip));
+        self.command.push_str("\\selectfont");
+    }
+
+    /// Add OpenType features and variable font axes to the font family declaration.
+    ///
+    /// Each feature is an OpenType feature tag, optionally with a value, e.g. `"onum"` (old-style numerals), `"dlig"` (discretionary ligatures), `"ss01"` (stylistic set 1), `"mark"` (mark positioning), or `"salt=2"`.
+    /// Prefix a feature with `-` to disable it, e.g. `"-liga"`.
+    /// Each axis is an axis tag with a value, e.g. `"wght=700"`, and is added as `+axis=wght:700`.
+    /// The features should already have been validated with [`CosmicFont::parse_features`].
+    pub fn set_features(&mut self, features: &[String]) {
+        if features.is_empty() {
+            return;
+        }
+        let raw_feature = features
+            .iter()
+            .map(|f| {
+                if CosmicFont::is_axis(f) {
+                    format!("+axis={}", f.replacen('=', ":", 1))
+                } else if f.starts_with('+') || f.starts_with('-') || f.contains('=') {
+                    f.to_string()
+                } else {
+                    format!("+{}", f)
+                }
+            })
+            .collect::<Vec<String>>()
+            .join(";");
+        self.options.push(format!("RawFeature={{{}}}", raw_feature));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TexFont;
+    use crate::font::{font_metrics::FontMetrics, synthetic::Synthetic};
+
+    #[test]
+    fn test_family() {
+        let tex_font = TexFont::new_from_family(
+            "rightfont",
+            "Frank Ruehl CLM",
+            "Medium",
+            &None,
+            &Some("Bold".to_string()),
+            &None,
+        );
+        assert_eq!(
+            tex_font.font_family(),
+            "\\newfontfamily\\rightfont[Ligatures=TeX, UprightFont={* Medium}, ItalicFont={* Medium}, BoldFont={* Bold}, BoldItalicFont={* Bold}]{Frank Ruehl CLM}"
+        );
+        assert_eq!(tex_font.command, "\\rightfont");
+    }
+
+    #[test]
+    fn test_features() {
+        let mut tex_font = TexFont::new("leftfont", "fonts", "regular.ttf", &None, &None, &None);
+        tex_font.set_features(&[
+            "onum".to_string(),
+            "-liga".to_string(),
+            "salt=2".to_string(),
+            "wght=700".to_string(),
+        ]);
+        assert_eq!(
+            tex_font.font_family(),
+            "\\newfontfamily\\leftfont[Path=fonts/, Ligatures=TeX, RawFeature={+onum;-liga;salt=2;+axis=wght:700}]{regular.ttf}"
+        );
+    }
+
+    #[test]
+    fn test_face_indices() {
+        let mut tex_font = TexFont::new(
+            "leftfont",
+            "fonts",
+            "font.ttc",
+            &Some("font.ttc".to_string()),
+            &None,
+            &None,
+        );
+        tex_font.set_face_indices([0, 1, 0, 0]);
+        assert_eq!(
+            tex_font.font_family(),
+            "\\newfontfamily\\leftfont[Path=fonts/, Ligatures=TeX, UprightFeatures={FontIndex=0}, ItalicFeatures={FontIndex=1}, BoldFeatures={FontIndex=0}, BoldItalicFeatures={FontIndex=0}, ItalicFont=font.ttc]{font.ttc}"
+        );
+        // Synthetic styles are added to the existing style features.
+        tex_font.set_synthetic(&Synthetic::get_styles(true, false, false));
+        assert!(tex_font
+            .font_family()
+            .contains("BoldFeatures={FontIndex=0, FakeBold=1.5}, BoldItalicFeatures={FontIndex=0, FakeBold=1.5}"));
+    }
+
+    #[test]
+    fn test_metrics() {
+        let mut tex_font = TexFont::new("leftfont", "fonts", "regular.ttf", &None, &None, &None);
+        tex_font.set_metrics(&FontMetrics {
+            size: 9.,
+            skip: 11.,
+        });
+        assert_eq!(tex_font.command, "\\leftfont\\fontsize{9}{11}\\selectfont");
+    }
 }
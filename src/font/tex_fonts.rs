@@ -4,11 +4,24 @@ use std::io;
 #[cfg(feature = "default-fonts")]
 use super::default_tex_fonts::DefaultTexFonts;
 use super::{fonts::Fonts, tex_font::TexFont};
+use crate::{error::Error, table::position::Position};
 
 pub struct TexFonts {
     pub left: TexFont,
     pub center: TexFont,
     pub right: TexFont,
+    /// Fonts for text tagged with a language.
+    pub languages: Vec<TexFont>,
+    /// Fonts for characters that the column fonts don't have.
+    pub fallbacks: Vec<TexFont>,
+    /// Fallback fonts of the left, center, and right columns.
+    pub column_fallbacks: [Vec<TexFont>; 3],
+    /// The font of the title.
+    pub title: Option<TexFont>,
+    /// The font of marginalia.
+    pub marginalia: Option<TexFont>,
+    /// The font of running headers and footers.
+    pub running_heads: Option<TexFont>,
     #[cfg(feature = "default-fonts")]
     pub(crate) _default_tex_fonts: Option<DefaultTexFonts>,
 }
@@ -30,22 +43,51 @@ impl From<DefaultTexFonts> for TexFonts {
             left,
             center,
             right,
+            languages: vec![],
+            fallbacks: vec![],
+            column_fallbacks: Default::default(),
+            title: None,
+            marginalia: None,
+            running_heads: None,
             _default_tex_fonts: Some(value),
         }
     }
 }
 
-impl From<&Fonts> for TexFonts {
-    fn from(value: &Fonts) -> Self {
-        let left = value.left.to_tex("leftfont");
-        let center = value.center.to_tex("centerfont");
-        let right = value.right.to_tex("rightfont");
-        Self {
+impl TexFonts {
+    /// Returns the fallback chain of the column at `position`: the column's fallback fonts, followed by the fallback fonts that every column uses.
+    pub(crate) fn get_fallback_chain(&self, position: Position) -> Vec<&TexFont> {
+        let [left, center, right] = &self.column_fallbacks;
+        match position {
+            Position::Left => left,
+            Position::Center => center,
+            Position::Right => right,
+        }
+        .iter()
+        .chain(self.fallbacks.iter())
+        .collect()
+    }
+}
+
+impl TryFrom<&Fonts> for TexFonts {
+    type Error = Error;
+
+    fn try_from(value: &Fonts) -> Result<Self, Self::Error> {
+        let left = value.left.font.to_tex("leftfont")?;
+        let center = value.center.font.to_tex("centerfont")?;
+        let right = value.right.font.to_tex("rightfont")?;
+        Ok(Self {
             left,
             center,
             right,
+            languages: value.language_tex_fonts()?,
+            fallbacks: value.fallback_tex_fonts()?,
+            column_fallbacks: value.column_fallback_tex_fonts()?,
+            title: value.title_tex_font()?,
+            marginalia: value.marginalia_tex_font()?,
+            running_heads: value.running_heads_tex_font()?,
             #[cfg(feature = "default-fonts")]
             _default_tex_fonts: None,
-        }
+        })
     }
 }
@@ -5,19 +5,30 @@ doc = embed_doc_image::embed_image!("daf", "images/daf.jpg"),
 doc = embed_doc_image::embed_image!("four_rows", "images/four_rows.jpg"),
 doc = embed_doc_image::embed_image!("center", "images/center.jpg"))]
 
-use std::{fs::read, path::Path};
+use std::{collections::BTreeSet, fs::read, path::Path};
 
 use error::Error;
 use serde::{Deserialize, Serialize};
 use serde_json::from_slice;
-use text::{Daf, SourceText};
+use text::{Cartouche, Daf, SourceText, Title};
 use xetex::get_pdf;
 
 use crate::{
-    font::fonts::Fonts,
-    page::Page,
-    span::Span,
-    table::{maybe_span_column::MaybeSpanColumn, span_column::SpanColumn, OptionalColumn, Table},
+    font::{
+        cosmic_font::CosmicFont,
+        fallback::CosmicFallback,
+        fonts::Fonts,
+        glyph_coverage::{GlyphCoverage, MissingGlyph},
+        tex_fonts::TexFonts,
+    },
+    page::{Page, RunningHeads},
+    span::{language::Language, style::Style, Span},
+    table::{
+        maybe_span_column::MaybeSpanColumn,
+        position::{Position, POSITIONS},
+        span_column::SpanColumn,
+        OptionalColumn, Table,
+    },
 };
 
 mod error;
@@ -65,9 +76,12 @@ pub struct Talmudifier {
     /// Raw markdown text that will be talmudified.
     source_text: SourceText,
     /// If not None, the title will be at the top of the page.
-    title: Option<String>,
+    title: Option<Title>,
     /// If true, logging is enabled.
     log: bool,
+    /// If true, `tex` code in the source text is emitted verbatim.
+    #[serde(default)]
+    raw_tex: bool,
 }
 
 impl Talmudifier {
@@ -102,7 +116,13 @@ impl Talmudifier {
 
     /// Set the title text. By default, there is no title.
     pub fn title<S: ToString>(mut self, title: S) -> Self {
-        self.title = Some(title.to_string());
+        self.title = Some(Title::Text(title.to_string()));
+        self
+    }
+
+    /// Set a title with a subtitle, chapter line, and frame. By default, there is no title.
+    pub fn cartouche(mut self, cartouche: Cartouche) -> Self {
+        self.title = Some(Title::Cartouche(cartouche));
         self
     }
 
@@ -116,36 +136,94 @@ impl Talmudifier {
         self
     }
 
+    /// Enable raw TeX passthrough.
+    /// Inline code that starts with `tex:` and fenced code blocks with a `tex` info string will be emitted verbatim.
+    /// This is an escape hatch for power users; invalid TeX will cause typesetting to fail.
+    pub fn raw_tex(mut self) -> Self {
+        self.raw_tex = true;
+        self
+    }
+
+    /// Check that every character in the source text can be typeset with the column fonts or the fallback fonts.
+    /// This doesn't run TeX, so it's much faster than [`Self::talmudify`].
+    ///
+    /// Returns every character that can't be typeset.
+    pub fn check_glyphs(&self) -> Result<Vec<MissingGlyph>, Error> {
+        let spans = self.get_spans()?;
+        let cosmic_fonts = self.fonts.cosmic_fonts(&self.page.font_metrics)?;
+        let languages = self.fonts.get_font_languages();
+        let marginalia = self.fonts.marginalia_cosmic_font()?;
+        let mut missing = vec![];
+        for (position, (span, font)) in POSITIONS.into_iter().zip(spans.iter().zip([
+            &cosmic_fonts.left,
+            &cosmic_fonts.center,
+            &cosmic_fonts.right,
+        ])) {
+            let fallbacks = self
+                .fonts
+                .fallback_cosmic_fonts(position, &self.page.font_metrics)?;
+            missing.append(
+                &mut GlyphCoverage::new(
+                    span,
+                    position,
+                    font,
+                    marginalia.as_ref().map(|(font, style)| (font, *style)),
+                    &fallbacks,
+                    &languages,
+                )
+                .missing,
+            );
+        }
+        Ok(missing)
+    }
+
     /// Convert raw markdown text into a Talmud page.
     /// This can take a while (on the other of minutes).
     /// Be patient!
     ///
     /// Returns a `Daf` containing the TeX string and the PDF.
     pub fn talmudify(&self) -> Result<Daf, Error> {
+        // In grid mode, every column is on the page's baseline grid.
+        let grid_fonts;
+        let fonts = if self.page.baseline_grid {
+            grid_fonts = self.fonts.snap_to_grid(self.page.font_metrics.skip);
+            &grid_fonts
+        } else {
+            &self.fonts
+        };
+
         // Get the TeX fonts.
-        let tex_fonts = self.fonts.tex_fonts()?;
+        let tex_fonts = fonts.tex_fonts()?;
+
+        // Get the words.
+        let [left_span, center_span, right_span] = self.get_spans()?;
+
+        // Get the languages that are tagged in the text.
+        let mut languages = [&left_span, &center_span, &right_span]
+            .iter()
+            .flat_map(|span| span.0.iter().filter_map(|word| word.language))
+            .collect::<BTreeSet<Language>>();
+        // Hebrew numerals and text in the title are typeset with polyglossia.
+        if self
+            .title
+            .as_ref()
+            .is_some_and(|title| title.requires_hebrew())
+        {
+            languages.insert(Language::Hebrew);
+        }
+        let languages = languages.into_iter().collect::<Vec<Language>>();
 
         // Clone the page.
         let mut page = self.page.clone();
-        // Set the preamble using the font definitions.
-        page.set_preamble(&tex_fonts);
 
         // Set the table width.
-        page.set_table_width();
-
-        // Set the preamble.
-        page.set_preamble(&tex_fonts);
+        page.set_table_width()?;
 
-        // Get the raw text.
-        let raw_text = self.source_text.get_text()?;
-
-        // Get the words.
-        let left_span = Span::from_md(&raw_text.left)?;
-        let center_span = Span::from_md(&raw_text.center)?;
-        let right_span = Span::from_md(&raw_text.right)?;
+        // Set the preamble using the font definitions.
+        page.set_preamble(&tex_fonts, &languages)?;
 
         // Get the cosmic fonts.
-        let cosmic_fonts = self.fonts.cosmic_fonts(&page.font_metrics)?;
+        let cosmic_fonts = fonts.cosmic_fonts(&page.font_metrics)?;
 
         // Get the columns.
         let mut left = SpanColumn::new(left_span, cosmic_fonts.left, &tex_fonts.left.command);
@@ -153,6 +231,48 @@ impl Talmudifier {
             SpanColumn::new(center_span, cosmic_fonts.center, &tex_fonts.center.command);
         let mut right = SpanColumn::new(right_span, cosmic_fonts.right, &tex_fonts.right.command);
 
+        // Set the marginalia font.
+        if let Some(marginalia) = &tex_fonts.marginalia {
+            for column in [&mut left, &mut center, &mut right] {
+                column.marginalia_font = Some(marginalia.command.clone());
+            }
+        }
+
+        // Use the fallback fonts for characters that the column fonts don't have or that are in a fallback font's ranges.
+        let languages = fonts.get_font_languages();
+        let marginalia = fonts.marginalia_cosmic_font()?;
+        // Text in a language without its own font is set in the column's font.
+        for column in [&mut left, &mut center, &mut right] {
+            column.font_languages = languages.clone();
+        }
+        for (position, column) in POSITIONS
+            .into_iter()
+            .zip([&mut left, &mut center, &mut right])
+        {
+            let fallbacks = fonts.fallback_cosmic_fonts(position, &page.font_metrics)?;
+            if !fallbacks.is_empty() {
+                Self::set_fallbacks(
+                    column,
+                    position,
+                    marginalia.as_ref().map(|(font, style)| (font, *style)),
+                    fallbacks,
+                    &tex_fonts,
+                    &languages,
+                );
+            }
+        }
+
+        // Measure the words whose widths are changed by the fonts' OpenType features.
+        for column in [&mut left, &mut center, &mut right] {
+            column.set_feature_scales();
+        }
+
+        // In two-sided printing, the inner commentary is on the left of recto pages.
+        // paracol swaps the columns on verso pages.
+        if let Some(two_sided) = &page.two_sided {
+            two_sided.arrange(&mut left, &mut right);
+        }
+
         let mut tables = vec![];
 
         // First four lines.
@@ -206,7 +326,8 @@ impl Talmudifier {
                     &page,
                     self.log,
                 );
-                match table.get_title_table(title)? {
+                let title_font = tex_fonts.title.as_ref().map(|font| font.command.as_str());
+                match table.get_title_table(&title.to_tex(title_font))? {
                     Some(table) => tables.push(table),
                     None => done = true,
                 }
@@ -217,6 +338,15 @@ impl Talmudifier {
             done = table.done();
         }
 
+        // The ornament between the opening block and the body.
+        let mut ornament_index = None;
+        if !done {
+            if let Some(ornament) = &page.ornament {
+                ornament_index = Some(tables.len());
+                tables.push(ornament.to_tex(&tex_fonts.center.command));
+            }
+        }
+
         while !done {
             // Decide which columns to use.
             let left_column = Self::get_column(&mut left);
@@ -278,8 +408,38 @@ impl Talmudifier {
         // Build the document.
         let mut tex = page.preamble.clone().unwrap();
 
-        // Add the tables.
-        tex.push_str(&tables.join("\n"));
+        // Show the running headers and footers.
+        if page.running_heads.is_some() {
+            tex.push_str(RunningHeads::PAGE_STYLE);
+        }
+
+        // Draw the crop marks and set the PDF boxes.
+        if let Some(prepress) = &page.prepress {
+            tex.push_str(&prepress.get_commands(&page.paper_size, &page.font_metrics)?);
+        }
+
+        // Draw the column rules.
+        if let Some(column_rules) = &page.column_rules {
+            tex.push_str(&column_rules.get_commands());
+        }
+
+        // Add the tables, with a horizontal rule between each table, except next to the ornament.
+        let row_rule = page
+            .column_rules
+            .as_ref()
+            .and_then(|column_rules| column_rules.get_row_rule());
+        for (i, table) in tables.iter().enumerate() {
+            if i > 0 {
+                tex.push('\n');
+                if let Some(row_rule) = &row_rule {
+                    if !ornament_index.is_some_and(|index| index == i || index + 1 == i) {
+                        tex.push_str(row_rule);
+                        tex.push('\n');
+                    }
+                }
+            }
+            tex.push_str(table);
+        }
         // End the document.
         tex.push_str(Page::END_DOCUMENT);
 
@@ -288,6 +448,49 @@ impl Talmudifier {
         Ok(Daf { tex, pdf })
     }
 
+    /// Get the words of each column from the source text.
+    fn get_spans(&self) -> Result<[Span; 3], Error> {
+        let raw_text = self.source_text.get_text()?;
+        Ok([
+            Span::from_md(&raw_text.left, self.raw_tex)?,
+            Span::from_md(&raw_text.center, self.raw_tex)?,
+            Span::from_md(&raw_text.right, self.raw_tex)?,
+        ])
+    }
+
+    /// Set the fallback fonts of the characters in a column.
+    /// `fallbacks` is the column's fallback chain.
+    fn set_fallbacks(
+        column: &mut SpanColumn,
+        position: Position,
+        marginalia: Option<(&CosmicFont, Style)>,
+        fallbacks: Vec<CosmicFallback>,
+        tex_fonts: &TexFonts,
+        languages: &[Language],
+    ) {
+        let coverage = GlyphCoverage::new(
+            &column.span,
+            position,
+            &column.cosmic_font,
+            marginalia,
+            &fallbacks,
+            languages,
+        );
+        let tex_fallbacks = tex_fonts.get_fallback_chain(position);
+        // Cosmic measures each fallback character in the fallback font that TeX sets it in.
+        for index in coverage.fallbacks.values() {
+            column
+                .fallback_fonts
+                .entry(tex_fallbacks[*index].command.clone())
+                .or_insert_with(|| fallbacks[*index].font.clone());
+        }
+        column.fallbacks = coverage
+            .fallbacks
+            .into_iter()
+            .map(|(key, index)| (key, tex_fallbacks[index].command.clone()))
+            .collect();
+    }
+
     fn get_column(span_column: &mut SpanColumn) -> OptionalColumn<'_> {
         if span_column.done() {
             None
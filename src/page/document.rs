@@ -0,0 +1,259 @@
+use serde::{Deserialize, Serialize};
+
+use crate::tex;
+
+use super::two_sided::TwoSided;
+
+/// A LaTeX package.
+#[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq)]
+pub struct Package {
+    /// The name of the package, e.g. `xcolor`.
+    pub name: String,
+    /// The package options.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub options: Vec<String>,
+}
+
+impl Package {
+    pub fn new<S: ToString>(name: S, options: &[&str]) -> Self {
+        Self {
+            name: name.to_string(),
+            options: options.iter().map(|option| option.to_string()).collect(),
+        }
+    }
+}
+
+/// PDF metadata.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct Metadata {
+    /// The title of the PDF.
+    #[serde(default)]
+    pub title: Option<String>,
+    /// The author of the PDF.
+    #[serde(default)]
+    pub author: Option<String>,
+    /// The subject of the PDF.
+    #[serde(default)]
+    pub subject: Option<String>,
+    /// Comma-separated keywords.
+    #[serde(default)]
+    pub keywords: Option<String>,
+}
+
+impl Metadata {
+    /// Returns the hyperref declarations that set the PDF's document info.
+    /// hyperref converts the text to PDF strings, including parentheses and non-ASCII text such as Hebrew.
+    fn get_hypersetup(&self) -> Option<String> {
+        let info = [
+            ("pdftitle", &self.title),
+            ("pdfauthor", &self.author),
+            ("pdfsubject", &self.subject),
+            ("pdfkeywords", &self.keywords),
+        ]
+        .into_iter()
+        .filter_map(|(key, value)| {
+            value
+                .as_ref()
+                .map(|value| format!("{}={{{}}}", key, Self::escape(value)))
+        })
+        .collect::<Vec<String>>();
+        if info.is_empty() {
+            None
+        } else {
+            Some(format!(
+                "{}\n{}",
+                tex!("usepackage", "hyperref"),
+                tex!("hypersetup", info.join(", "))
+            ))
+        }
+    }
+
+    /// Escape TeX's special characters in `value`.
+    fn escape(value: &str) -> String {
+        value
+            .chars()
+            .map(|c| match c {
+                '\\' => "\\textbackslash{}".to_string(),
+                '~' => "\\textasciitilde{}".to_string(),
+                '^' => "\\textasciicircum{}".to_string(),
+                '{' | '}' | '#' | '$' | '%' | '&' | '_' => format!("\\{}", c),
+                c => c.to_string(),
+            })
+            .collect()
+    }
+}
+
+/// The document class, packages, and custom TeX.
+///
+/// These are added to every document, both when measuring lines and when generating the final PDF, so line counts stay correct.
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Document {
+    /// The document class.
+    pub class: String,
+    /// The document class options.
+    pub class_options: Vec<String>,
+    /// Additional packages. These are loaded after Talmudifier's packages.
+    /// The options are passed to the package before Talmudifier loads it, so they don't clash with Talmudifier's options.
+    pub packages: Vec<Package>,
+    /// TeX that is added to the end of the preamble.
+    pub preamble: String,
+    /// TeX that is added to the start of the body.
+    pub body_start: String,
+    /// Optional PDF metadata. This is set with hyperref.
+    pub metadata: Option<Metadata>,
+}
+
+impl Document {
+    /// Returns the `\documentclass` declaration,
+    /// followed by the options of the additional packages.
+    /// If `two_sided` is true, the class is two-sided even if the class options include `oneside`.
+    pub(crate) fn get_document_class(&self, two_sided: bool) -> String {
+        let class_options = if two_sided {
+            TwoSided::get_class_options(&self.class_options)
+        } else {
+            self.class_options.clone()
+        };
+        let mut document_class = if class_options.is_empty() {
+            tex!("documentclass", &self.class)
+        } else {
+            format!(
+                "\\documentclass[{}]{{{}}}",
+                class_options.join(", "),
+                self.class
+            )
+        };
+        for package in self
+            .packages
+            .iter()
+            .filter(|package| !package.options.is_empty())
+        {
+            document_class.push('\n');
+            document_class += &tex!(
+                "PassOptionsToPackage",
+                package.options.join(","),
+                package.name
+            );
+        }
+        document_class
+    }
+
+    /// Returns the declarations of the additional packages, the metadata, and the custom preamble.
+    pub(crate) fn get_preamble(&self) -> String {
+        let mut preamble = self
+            .packages
+            .iter()
+            .map(|package| format!("\n{}", tex!("usepackage", package.name)))
+            .collect::<String>();
+        // hyperref is loaded after the other packages.
+        if let Some(hypersetup) = self.metadata.as_ref().and_then(|m| m.get_hypersetup()) {
+            preamble.push('\n');
+            preamble += &hypersetup;
+        }
+        if !self.preamble.is_empty() {
+            preamble.push('\n');
+            preamble += &self.preamble;
+        }
+        preamble
+    }
+
+    /// Returns the custom TeX at the start of the body.
+    pub(crate) fn get_body_start(&self) -> String {
+        let mut body_start = String::new();
+        if !self.body_start.is_empty() {
+            body_start += &self.body_start;
+            body_start.push('\n');
+        }
+        body_start
+    }
+}
+
+impl Default for Document {
+    fn default() -> Self {
+        Self {
+            class: "scrbook".to_string(),
+            class_options: vec!["11pt".to_string(), "openany".to_string()],
+            packages: vec![],
+            preamble: String::default(),
+            body_start: String::default(),
+            metadata: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::from_str;
+
+    use crate::get_pdf;
+
+    use super::{Document, Metadata, Package};
+
+    #[test]
+    fn test_document() {
+        let document = Document::default();
+        assert_eq!(
+            document.get_document_class(false),
+            "\\documentclass[11pt, openany]{scrbook}"
+        );
+        assert_eq!(
+            document.get_document_class(true),
+            "\\documentclass[11pt, openany, twoside]{scrbook}"
+        );
+        assert!(document.get_preamble().is_empty());
+        assert!(document.get_body_start().is_empty());
+
+        let document = Document {
+            class: "book".to_string(),
+            class_options: vec![],
+            packages: vec![
+                Package::new("xcolor", &["dvipsnames", "table"]),
+                Package::new("lipsum", &[]),
+            ],
+            preamble: "\\setlength\\parindent{1em}".to_string(),
+            body_start: "\\color{gray}".to_string(),
+            metadata: Some(Metadata {
+                title: Some("Berakhot (2a)".to_string()),
+                author: Some("Rav Ashi".to_string()),
+                ..Default::default()
+            }),
+        };
+        assert_eq!(
+            document.get_document_class(false),
+            "\\documentclass{book}\n\\PassOptionsToPackage{dvipsnames,table}{xcolor}"
+        );
+        assert_eq!(
+            document.get_preamble(),
+            "\n\\usepackage{xcolor}\n\\usepackage{lipsum}\n\\usepackage{hyperref}\n\\hypersetup{pdftitle={Berakhot (2a)}, pdfauthor={Rav Ashi}}\n\\setlength\\parindent{1em}"
+        );
+        assert_eq!(document.get_body_start(), "\\color{gray}\n");
+
+        // Missing fields are the default values.
+        let document: Document = from_str(r#"{"class": "book"}"#).unwrap();
+        assert_eq!(document.class, "book");
+        assert_eq!(document.class_options, vec!["11pt", "openany"]);
+    }
+
+    #[test]
+    fn test_metadata() {
+        let metadata = Metadata {
+            title: Some("Berakhot (2a) 100% #1 {x} \\ ~".to_string()),
+            author: Some("רב אשי".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            metadata.get_hypersetup().unwrap(),
+            "\\usepackage{hyperref}\n\\hypersetup{pdftitle={Berakhot (2a) 100\\% \\#1 \\{x\\} \\textbackslash{} \\textasciitilde{}}, pdfauthor={רב אשי}}"
+        );
+        let document = Document {
+            metadata: Some(metadata),
+            ..Default::default()
+        };
+        let tex = format!(
+            "{}{}\n\\begin{{document}}\nBerakhot\n\\end{{document}}",
+            document.get_document_class(false),
+            document.get_preamble()
+        );
+        get_pdf(&tex).unwrap();
+    }
+}
@@ -0,0 +1,97 @@
+use serde::{Deserialize, Serialize};
+
+use crate::span::language::Language;
+
+/// Hyphenation and microtypography settings.
+///
+/// These are set in the preamble, so the same settings are used when measuring lines and when generating the final PDF.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct Hyphenation {
+    /// The language of untagged text. This sets the default hyphenation patterns.
+    pub language: Language,
+    /// If false, words are never hyphenated.
+    pub enabled: bool,
+    /// The minimum number of characters before a hyphen.
+    pub left_hyphen_min: u8,
+    /// The minimum number of characters after a hyphen.
+    /// Words that are shorter than `left_hyphen_min + right_hyphen_min` are never hyphenated.
+    pub right_hyphen_min: u8,
+    /// The penalty for breaking a line at a hyphen. Higher values mean fewer hyphens. 10000 means never.
+    pub hyphen_penalty: i32,
+    /// The penalty for breaking a line at an explicit hyphen, e.g. `self-evident`.
+    pub ex_hyphen_penalty: i32,
+    /// Additional demerits for two consecutive lines that end with hyphens.
+    pub double_hyphen_demerits: i32,
+    /// If true, punctuation and some letters protrude slightly into the margin.
+    pub protrusion: bool,
+    /// If true, fonts are slightly stretched or shrunk to reduce gaps between words.
+    /// microtype only supports expansion in pdfTeX and LuaTeX, so XeTeX ignores this.
+    pub expansion: bool,
+}
+
+impl Hyphenation {
+    /// Returns the microtype package declaration.
+    pub(crate) fn get_microtype(&self) -> String {
+        format!(
+            "\\usepackage[protrusion={}, expansion={}]{{microtype}}",
+            self.protrusion, self.expansion
+        )
+    }
+
+    /// Returns the commands that set the hyphenation parameters.
+    /// These must be set after `\begin{document}` because polyglossia resets the hyphen mins when it selects the default language.
+    pub(crate) fn get_commands(&self) -> String {
+        let (hyphen_penalty, ex_hyphen_penalty) = if self.enabled {
+            (self.hyphen_penalty, self.ex_hyphen_penalty)
+        } else {
+            (10000, 10000)
+        };
+        format!(
+            "\\lefthyphenmin={} \\righthyphenmin={} \\hyphenpenalty={} \\exhyphenpenalty={} \\doublehyphendemerits={}\n",
+            self.left_hyphen_min,
+            self.right_hyphen_min,
+            hyphen_penalty,
+            ex_hyphen_penalty,
+            self.double_hyphen_demerits
+        )
+    }
+}
+
+impl Default for Hyphenation {
+    /// TeX's default hyphenation parameters, with protrusion.
+    fn default() -> Self {
+        Self {
+            language: Language::English,
+            enabled: true,
+            left_hyphen_min: 2,
+            right_hyphen_min: 3,
+            hyphen_penalty: 50,
+            ex_hyphen_penalty: 50,
+            double_hyphen_demerits: 10000,
+            protrusion: true,
+            expansion: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Hyphenation;
+
+    #[test]
+    fn test_hyphenation() {
+        let mut hyphenation = Hyphenation::default();
+        assert_eq!(
+            hyphenation.get_commands(),
+            "\\lefthyphenmin=2 \\righthyphenmin=3 \\hyphenpenalty=50 \\exhyphenpenalty=50 \\doublehyphendemerits=10000\n"
+        );
+        hyphenation.enabled = false;
+        assert!(hyphenation
+            .get_commands()
+            .contains("\\hyphenpenalty=10000 \\exhyphenpenalty=10000"));
+        assert_eq!(
+            hyphenation.get_microtype(),
+            "\\usepackage[protrusion=true, expansion=false]{microtype}"
+        );
+    }
+}
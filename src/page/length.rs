@@ -1,11 +1,13 @@
-use std::fmt;
+use std::{fmt, str::FromStr};
 
 use serde::{Deserialize, Serialize};
 
-use super::unit::Unit;
+use crate::{error::Error, font::font_metrics::FontMetrics};
+
+use super::unit::{Unit, EX_PER_EM};
 
 /// A length, in a given unit.
-#[derive(Clone, Deserialize, Serialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
 pub struct Length {
     /// The scalar length.
     pub length: f32,
@@ -22,6 +24,14 @@ impl Length {
         }
     }
 
+    /// A length in millimeters.
+    pub fn mm(length: f32) -> Self {
+        Self {
+            length,
+            unit: Unit::Mm,
+        }
+    }
+
     /// A length in ems.
     pub fn em(length: f32) -> Self {
         Self {
@@ -38,20 +48,25 @@ impl Length {
         }
     }
 
-    /// Returns the length in pts.
-    pub fn get_pts(&self) -> f32 {
-        const PTS_IN: f32 = 72.27;
-        const PTS_MM: f32 = 2.85;
-        const PTS_CM: f32 = PTS_MM * 10.;
-
-        match &self.unit {
-            Unit::Pt => self.length,
-            Unit::Mm => self.length * PTS_MM,
-            Unit::Cm => self.length * PTS_CM,
-            Unit::In => self.length * PTS_IN,
-            other => panic!("Sorry, can't directly convert {} to pts", other),
+    /// Returns the length in pts. Ems and exes are relative to the size of `font_metrics`.
+    pub fn get_pts(&self, font_metrics: &FontMetrics) -> f32 {
+        match self.unit.pts() {
+            Some(pts) => self.length * pts,
+            None => match self.unit {
+                Unit::Ex => self.length * font_metrics.size * EX_PER_EM,
+                _ => self.length * font_metrics.size,
+            },
         }
     }
+
+    /// Returns the length in pts.
+    /// Returns an error if the length is relative to the font size, i.e. if the unit is `Em` or `Ex`.
+    pub fn get_absolute_pts(&self) -> Result<f32, Error> {
+        self.unit
+            .pts()
+            .map(|pts| self.length * pts)
+            .ok_or(Error::RelativeLength(self.to_string()))
+    }
 }
 
 impl fmt::Display for Length {
@@ -59,3 +74,69 @@ impl fmt::Display for Length {
         write!(f, "{}{}", self.length, self.unit)
     }
 }
+
+impl FromStr for Length {
+    type Err = Error;
+
+    /// Parse a length from a TeX-like string, e.g. `"1.5em"` or `"10 pt"`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        // Split the string into the number and the unit.
+        let index = s
+            .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '-'))
+            .ok_or(Error::Length(s.to_string()))?;
+        let (length, unit) = s.split_at(index);
+        let length = length
+            .parse::<f32>()
+            .map_err(|_| Error::Length(s.to_string()))?;
+        let unit = match unit.trim().to_lowercase().as_str() {
+            "pt" => Unit::Pt,
+            "mm" => Unit::Mm,
+            "cm" => Unit::Cm,
+            "in" => Unit::In,
+            "bp" => Unit::Bp,
+            "pc" => Unit::Pc,
+            "dd" => Unit::Dd,
+            "cc" => Unit::Cc,
+            "sp" => Unit::Sp,
+            "em" => Unit::Em,
+            "ex" => Unit::Ex,
+            _ => return Err(Error::Length(s.to_string())),
+        };
+        Ok(Self { length, unit })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::font::font_metrics::FontMetrics;
+
+    use super::{Length, Unit};
+
+    #[test]
+    fn test_length() {
+        let font_metrics = FontMetrics::default();
+        assert_eq!(Length::inches(1.).get_absolute_pts().unwrap(), 72.27);
+        assert_eq!(
+            Length::from_str("25.4mm").unwrap().get_pts(&font_metrics),
+            72.27
+        );
+        assert_eq!(
+            Length::from_str("72bp").unwrap().get_pts(&font_metrics),
+            72.27
+        );
+        assert_eq!(Length::from_str("1pc").unwrap().get_pts(&font_metrics), 12.);
+        assert_eq!(
+            Length::from_str("65536 sp").unwrap().get_pts(&font_metrics),
+            1.
+        );
+        // Ems and exes are relative to the font size.
+        assert_eq!(Length::em(2.).get_pts(&font_metrics), 22.);
+        let ex = Length::from_str("2ex").unwrap();
+        assert_eq!(ex.unit, Unit::Ex);
+        assert_eq!(ex.get_pts(&font_metrics), 11.);
+        assert!(ex.get_absolute_pts().is_err());
+    }
+}
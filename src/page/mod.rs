@@ -1,14 +1,30 @@
-use crate::{font::tex_fonts::TexFonts, prelude::FontMetrics, tex};
+use crate::{
+    error::Error, font::tex_fonts::TexFonts, prelude::FontMetrics, span::language::Language, tex,
+};
 
+pub use document::{Document, Metadata, Package};
+pub use hyphenation::Hyphenation;
 pub use length::Length;
 pub use margins::Margins;
+pub use numbering::Numbering;
 pub use paper_size::PaperSize;
+pub use prepress::Prepress;
+pub use rules::{ColumnRules, Ornament, RuleStyle};
+pub use running_heads::{RunningHead, RunningHeads, Side};
 use serde::{Deserialize, Serialize};
+pub use two_sided::{Commentary, Parity, TwoSided};
 pub use unit::Unit;
 
+mod document;
+mod hyphenation;
 mod length;
 mod margins;
+mod numbering;
 mod paper_size;
+mod prepress;
+mod rules;
+mod running_heads;
+mod two_sided;
 mod unit;
 
 /// Page layout parameters.
@@ -22,6 +38,30 @@ pub struct Page {
     pub column_separation: Length,
     /// The font size and line skip.
     pub font_metrics: FontMetrics,
+    /// Hyphenation and microtypography settings. If None, TeX's defaults are used.
+    #[serde(default)]
+    pub hyphenation: Option<Hyphenation>,
+    /// Running headers and footers. If None, there are no headers, footers, or page numbers.
+    #[serde(default)]
+    pub running_heads: Option<RunningHeads>,
+    /// Two-sided printing. If None, the commentaries are on the same sides on every page.
+    #[serde(default)]
+    pub two_sided: Option<TwoSided>,
+    /// Vertical rules between columns. If None, there are no rules.
+    #[serde(default)]
+    pub column_rules: Option<ColumnRules>,
+    /// An ornament between the opening block and the body. If None, there is no ornament.
+    #[serde(default)]
+    pub ornament: Option<Ornament>,
+    /// The document class, additional packages, and custom TeX.
+    #[serde(default)]
+    pub document: Document,
+    /// Crop marks, bleed, and a slug line. If None, the PDF's pages are the size of `paper_size`.
+    #[serde(default)]
+    pub prepress: Option<Prepress>,
+    /// If true, every column's lines are on the baseline grid of `font_metrics`.
+    #[serde(default)]
+    pub baseline_grid: bool,
     /// The width of the text portion of the page.
     #[serde(skip, default = "get_default_table_width")]
     pub(crate) table_width: f32,
@@ -33,38 +73,66 @@ pub struct Page {
 impl Page {
     pub(crate) const END_DOCUMENT: &str = "\n\\end{sloppypar}\\end{document}";
 
-    pub(crate) fn set_table_width(&mut self) {
-        self.table_width =
-            self.paper_size.width() - (self.margins.left.get_pts() + self.margins.right.get_pts());
+    /// Set the width of the text portion of the page.
+    /// Returns an error if the paper size is relative to the font size.
+    pub(crate) fn set_table_width(&mut self) -> Result<(), Error> {
+        self.table_width = self.paper_size.width()?
+            - (self.margins.left.get_pts(&self.font_metrics)
+                + self.margins.right.get_pts(&self.font_metrics));
+        Ok(())
     }
 
-    pub(crate) fn set_preamble(&mut self, fonts: &TexFonts) {
-        self.preamble = Some(Self::get_preamble(
-            fonts,
-            &self.paper_size,
-            &self.margins,
-            &self.column_separation,
-            &self.font_metrics,
-        ));
+    /// Set the preamble. `languages` are the languages that are tagged in the source text.
+    /// Returns an error if the paper size is relative to the font size.
+    pub(crate) fn set_preamble(
+        &mut self,
+        fonts: &TexFonts,
+        languages: &[Language],
+    ) -> Result<(), Error> {
+        self.preamble = Some(self.get_preamble(fonts, languages)?);
+        Ok(())
     }
 
-    fn get_preamble(
-        fonts: &TexFonts,
-        paper_size: &PaperSize,
-        margins: &Margins,
-        column_separation: &Length,
-        font_metrics: &FontMetrics,
-    ) -> String {
-        let mut preamble = format!("\\documentclass[11pt, {}, openany]{{scrbook}}", paper_size);
+    fn get_preamble(&self, fonts: &TexFonts, languages: &[Language]) -> Result<String, Error> {
+        let mut preamble = self.document.get_document_class(self.two_sided.is_some());
+        // With prepress, the paper size is the trim size, and the PDF's pages are larger.
+        let paper_size = match &self.prepress {
+            Some(prepress) => prepress.get_geometry(&self.paper_size, &self.font_metrics)?,
+            None => self.paper_size.to_string(),
+        };
         preamble += &format!(
-            "\n\\usepackage[{}, {}]{{geometry}}\n\\pagenumbering{{gobble}}\n\n",
-            paper_size, margins
+            "\n\\usepackage[{}, {}]{{geometry}}\n",
+            paper_size, self.margins
         );
+        // Without running heads, there are no page numbers.
+        if self.running_heads.is_none() {
+            preamble += "\\pagenumbering{gobble}\n";
+        }
+        preamble.push('\n');
         preamble += &["marginnote", "sectsty", "ragged2e", "paracol", "fontspec"]
             .iter()
             .map(|p| crate::tex!("usepackage", p))
             .collect::<Vec<String>>()
             .join("\n");
+        // Underline and strikethrough without replacing \emph.
+        preamble += "\n\\usepackage[normalem]{ulem}";
+        // Hebrew numerals in the running heads are typeset with polyglossia.
+        let mut languages = languages.to_vec();
+        if self
+            .running_heads
+            .as_ref()
+            .is_some_and(|running_heads| running_heads.requires_hebrew())
+        {
+            languages.push(Language::Hebrew);
+        }
+        // Hyphenation patterns, fonts, and direction per language.
+        if !languages.is_empty() || self.hyphenation.is_some() {
+            preamble += &self.get_polyglossia(&languages);
+        }
+        if let Some(hyphenation) = &self.hyphenation {
+            preamble.push('\n');
+            preamble += &hyphenation.get_microtype();
+        }
 
         preamble += "\n\n\\allsectionsfont{\\centering}\n\\setlength\\parindent{";
         preamble.push_str(&Length::pt(0.).to_string());
@@ -72,17 +140,87 @@ impl Page {
 
         for (keyword, length) in ["\\columnsep", "\\parfillskip"]
             .iter()
-            .zip([column_separation, &Length::pt(0.)])
+            .zip([&self.column_separation, &Length::pt(0.)])
         {
             preamble += &Self::set_length(keyword, length)
         }
+        // Lines are always exactly one line skip apart, even if they have tall glyphs.
+        if self.baseline_grid {
+            preamble += &Self::set_length("\\parskip", &Length::pt(0.));
+            preamble += "\n\\lineskiplimit=-\\maxdimen";
+        }
         preamble.push('\n');
-        for font in [&fonts.left, &fonts.center, &fonts.right].iter() {
-            preamble.push_str(&font.font_family);
+        for font in [&fonts.left, &fonts.center, &fonts.right]
+            .into_iter()
+            .chain(fonts.languages.iter())
+            .chain(fonts.column_fallbacks.iter().flatten())
+            .chain(fonts.fallbacks.iter())
+            .chain(fonts.title.iter())
+            .chain(fonts.marginalia.iter())
+            .chain(fonts.running_heads.iter())
+        {
+            preamble.push_str(&font.font_family());
+            preamble.push('\n');
+        }
+        if let Some(running_heads) = &self.running_heads {
+            let font = fonts.running_heads.as_ref().unwrap_or(&fonts.center);
+            let first_page = self
+                .two_sided
+                .as_ref()
+                .map_or(1, |two_sided| two_sided.first_page_number());
+            preamble += &running_heads.get_preamble(&font.command, first_page);
+            preamble.push('\n');
+        }
+        if self.two_sided.is_some() {
+            preamble.push_str(TwoSided::PREAMBLE);
+            preamble.push('\n');
+        }
+        if let Some(column_rules) = &self.column_rules {
+            preamble += &column_rules.get_preamble();
             preamble.push('\n');
         }
+        // Polyglossia doesn't define a font for Aramaic.
+        if languages.contains(&Language::Aramaic) {
+            preamble.push_str(&tex!("providecommand", "\\aramaicfont", ""));
+            preamble.push('\n');
+        }
+        // Custom packages and TeX.
+        preamble += &self.document.get_preamble();
         preamble += "\n\n\\raggedbottom\n\n\\begin{document}\\begin{sloppypar}\n\n";
-        preamble + &tex!("fontsize", font_metrics.size, font_metrics.skip)
+        if let Some(hyphenation) = &self.hyphenation {
+            preamble += &hyphenation.get_commands();
+        }
+        if let Some(two_sided) = &self.two_sided {
+            preamble += &two_sided.get_commands();
+        }
+        preamble += &self.document.get_body_start();
+        Ok(preamble + &tex!("fontsize", self.font_metrics.size, self.font_metrics.skip))
+    }
+
+    /// Load polyglossia and declare the languages.
+    /// The default language is set by `self.hyphenation`, or English if `self.hyphenation` is None.
+    fn get_polyglossia(&self, languages: &[Language]) -> String {
+        let default_language = match &self.hyphenation {
+            Some(hyphenation) => hyphenation.language,
+            None => Language::English,
+        };
+        let mut other_languages = languages
+            .iter()
+            .map(|language| language.polyglossia())
+            .filter(|language| *language != default_language.polyglossia())
+            .collect::<Vec<&str>>();
+        other_languages.sort();
+        other_languages.dedup();
+        let mut polyglossia = format!(
+            "\n{}\n{}",
+            tex!("usepackage", "polyglossia"),
+            tex!("setdefaultlanguage", default_language.polyglossia())
+        );
+        if !other_languages.is_empty() {
+            polyglossia.push('\n');
+            polyglossia += &tex!("setotherlanguages", other_languages.join(","));
+        }
+        polyglossia
     }
 
     fn set_length(keyword: &str, length: &Length) -> String {
@@ -104,25 +242,56 @@ impl Default for Page {
         let column_separation = Self::default_column_separation();
         let table_width = get_default_table_width();
 
-        let preamble = Page::get_preamble(
-            &TexFonts::new().unwrap(),
-            &paper_size,
-            &margins,
-            &column_separation,
-            &font_metrics,
-        );
-        Self {
+        let mut page = Self {
             paper_size,
             margins,
             column_separation,
             table_width,
-            preamble: Some(preamble),
+            preamble: None,
             font_metrics,
-        }
+            hyphenation: None,
+            running_heads: None,
+            two_sided: None,
+            column_rules: None,
+            ornament: None,
+            document: Document::default(),
+            prepress: None,
+            baseline_grid: false,
+        };
+        page.set_preamble(&TexFonts::new().unwrap(), &[]).unwrap();
+        page
     }
 }
 
 fn get_default_table_width() -> f32 {
     let margins = Margins::default();
-    PaperSize::Letter.width() - (margins.left.get_pts() + margins.right.get_pts())
+    let font_metrics = FontMetrics::default();
+    PaperSize::Letter.width().unwrap()
+        - (margins.left.get_pts(&font_metrics) + margins.right.get_pts(&font_metrics))
+}
+
+#[cfg(all(test, feature = "default-fonts"))]
+mod tests {
+    use crate::font::tex_fonts::TexFonts;
+
+    use super::{Commentary, Document, Page, Parity, TwoSided};
+
+    #[test]
+    fn test_two_sided_preamble() {
+        let page = Page {
+            two_sided: Some(TwoSided {
+                inner: Commentary::Right,
+                first_page: Parity::Verso,
+            }),
+            document: Document {
+                class_options: vec!["11pt".to_string(), "oneside".to_string()],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let preamble = page.get_preamble(&TexFonts::new().unwrap(), &[]).unwrap();
+        assert!(preamble.starts_with("\\documentclass[11pt, twoside]{scrbook}"));
+        assert!(preamble.contains("\n\\twosided[c]\n"));
+        assert!(preamble.contains("\\begin{document}\\begin{sloppypar}\n\n\\setcounter{page}{2}\n"));
+    }
 }
@@ -0,0 +1,219 @@
+use serde::{Deserialize, Serialize};
+
+use super::running_heads::Side;
+
+/// The Hebrew numeral letters of the ones, tens, and hundreds.
+const ONES: [char; 9] = ['א', 'ב', 'ג', 'ד', 'ה', 'ו', 'ז', 'ח', 'ט'];
+const TENS: [char; 9] = ['י', 'כ', 'ל', 'מ', 'נ', 'ס', 'ע', 'פ', 'צ'];
+const HUNDREDS: [char; 4] = ['ק', 'ר', 'ש', 'ת'];
+const GERESH: char = '\u{05F3}';
+const GERSHAYIM: char = '\u{05F4}';
+const ROMAN: [(u32, &str); 13] = [
+    (1000, "m"),
+    (900, "cm"),
+    (500, "d"),
+    (400, "cd"),
+    (100, "c"),
+    (90, "xc"),
+    (50, "l"),
+    (40, "xl"),
+    (10, "x"),
+    (9, "ix"),
+    (5, "v"),
+    (4, "iv"),
+    (1, "i"),
+];
+
+/// How folios and pages are numbered.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, Eq, PartialEq)]
+pub enum Numbering {
+    /// 1, 2, 3, etc.
+    #[default]
+    Arabic,
+    /// i, ii, iii, etc.
+    Roman,
+    /// I, II, III, etc.
+    UpperRoman,
+    /// Hebrew numerals: א׳, ב׳, ג׳, etc.
+    Hebrew,
+}
+
+impl Numbering {
+    /// Returns `n` as a string. Zero is an empty string, except in Arabic numerals.
+    pub fn format(&self, n: u32) -> String {
+        match self {
+            Self::Arabic => n.to_string(),
+            Self::Roman => Self::to_roman(n),
+            Self::UpperRoman => Self::to_roman(n).to_uppercase(),
+            Self::Hebrew => Self::to_hebrew(n),
+        }
+    }
+
+    /// Returns the suffix of a side of a folio: a or b, or ע״א or ע״ב in Hebrew.
+    pub fn format_side(&self, side: Side) -> &'static str {
+        match (self, side) {
+            (Self::Hebrew, Side::A) => "ע״א",
+            (Self::Hebrew, Side::B) => "ע״ב",
+            (_, Side::A) => "a",
+            (_, Side::B) => "b",
+        }
+    }
+
+    /// Returns a folio and its side, e.g. 2a or ב׳ ע״א.
+    pub fn format_folio(&self, folio: u32, side: Side) -> String {
+        format!(
+            "{}{}{}",
+            self.format(folio),
+            self.get_side_separator(),
+            self.format_side(side)
+        )
+    }
+
+    /// The text between a folio and its side.
+    pub(crate) const fn get_side_separator(&self) -> &'static str {
+        match self {
+            Self::Hebrew => " ",
+            _ => "",
+        }
+    }
+
+    /// Returns true if the numerals are Hebrew, and must be typeset right to left.
+    pub(crate) const fn is_hebrew(&self) -> bool {
+        matches!(self, Self::Hebrew)
+    }
+
+    /// Wrap `tex` in a command that sets its direction.
+    pub(crate) fn wrap(&self, tex: &str) -> String {
+        if self.is_hebrew() {
+            format!("\\texthebrew{{{}}}", tex)
+        } else {
+            format!("{{{}}}", tex)
+        }
+    }
+
+    /// Returns TeX that expands to the numeral of `number`, which is a TeX integer expression.
+    /// `arabic` is the TeX of the Arabic numeral.
+    ///
+    /// Roman numerals are converted by TeX's `\romannumeral`, and Hebrew numerals by polyglossia's `\Hebrewnumeral`.
+    pub(crate) fn get_tex(&self, number: &str, arabic: &str) -> String {
+        match self {
+            Self::Arabic => arabic.to_string(),
+            Self::Roman => format!("\\romannumeral\\numexpr {}\\relax", number),
+            Self::UpperRoman => format!(
+                "\\uppercase\\expandafter{{\\romannumeral\\numexpr {}\\relax}}",
+                number
+            ),
+            Self::Hebrew => format!("\\Hebrewnumeral{{\\number\\numexpr {}\\relax}}", number),
+        }
+    }
+
+    fn to_roman(mut n: u32) -> String {
+        let mut roman = String::new();
+        for (value, numeral) in ROMAN {
+            while n >= value {
+                roman.push_str(numeral);
+                n -= value;
+            }
+        }
+        roman
+    }
+
+    fn to_hebrew(n: u32) -> String {
+        if n == 0 {
+            return String::new();
+        }
+        let mut hebrew = String::new();
+        // Thousands are written as a numeral followed by a geresh.
+        if n >= 1000 {
+            hebrew += &Self::get_hebrew_letters(n / 1000);
+            hebrew.push(GERESH);
+        }
+        let letters = Self::get_hebrew_letters(n % 1000);
+        let mut chars = letters.chars();
+        match (chars.next_back(), chars.as_str()) {
+            (None, _) => (),
+            // A single letter is followed by a geresh.
+            (Some(last), "") => {
+                hebrew.push(last);
+                hebrew.push(GERESH);
+            }
+            // Gershayim go before the last letter.
+            (Some(last), rest) => {
+                hebrew += rest;
+                hebrew.push(GERSHAYIM);
+                hebrew.push(last);
+            }
+        }
+        hebrew
+    }
+
+    /// Returns the letters of `n % 1000` without punctuation.
+    fn get_hebrew_letters(n: u32) -> String {
+        let mut letters = String::new();
+        let mut hundreds = (n % 1000) / 100;
+        while hundreds >= 4 {
+            letters.push(HUNDREDS[3]);
+            hundreds -= 4;
+        }
+        if hundreds > 0 {
+            letters.push(HUNDREDS[hundreds as usize - 1]);
+        }
+        // 15 and 16 are written as 9 + 6 and 9 + 7 to avoid spelling the name of God.
+        match n % 100 {
+            15 => letters += "טו",
+            16 => letters += "טז",
+            n => {
+                if n >= 10 {
+                    letters.push(TENS[(n / 10) as usize - 1]);
+                }
+                if n % 10 > 0 {
+                    letters.push(ONES[(n % 10) as usize - 1]);
+                }
+            }
+        }
+        letters
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::page::Side;
+
+    use super::Numbering;
+
+    #[test]
+    fn test_numbering() {
+        let hebrew = Numbering::Hebrew;
+        assert_eq!(hebrew.format(0), "");
+        assert_eq!(hebrew.format(2), "ב׳");
+        assert_eq!(hebrew.format(10), "י׳");
+        assert_eq!(hebrew.format(15), "ט״ו");
+        assert_eq!(hebrew.format(16), "ט״ז");
+        assert_eq!(hebrew.format(64), "ס״ד");
+        assert_eq!(hebrew.format(115), "קט״ו");
+        assert_eq!(hebrew.format(176), "קע״ו");
+        assert_eq!(hebrew.format(400), "ת׳");
+        assert_eq!(hebrew.format(770), "תש״ע");
+        assert_eq!(hebrew.format(5786), "ה׳תשפ״ו");
+        assert_eq!(hebrew.format_folio(2, Side::A), "ב׳ ע״א");
+        assert_eq!(Numbering::Roman.format(1994), "mcmxciv");
+        assert_eq!(Numbering::UpperRoman.format(4), "IV");
+        assert_eq!(Numbering::Arabic.format_folio(2, Side::B), "2b");
+        assert_eq!(
+            Numbering::Arabic.get_tex("\\value{page}", "\\arabic{page}"),
+            "\\arabic{page}"
+        );
+        assert_eq!(
+            Numbering::Roman.get_tex("\\value{page}", "\\arabic{page}"),
+            "\\romannumeral\\numexpr \\value{page}\\relax"
+        );
+        assert_eq!(
+            Numbering::UpperRoman.get_tex("\\value{page}", "\\arabic{page}"),
+            "\\uppercase\\expandafter{\\romannumeral\\numexpr \\value{page}\\relax}"
+        );
+        assert_eq!(
+            Numbering::Hebrew.get_tex("\\value{page}", "\\arabic{page}"),
+            "\\Hebrewnumeral{\\number\\numexpr \\value{page}\\relax}"
+        );
+    }
+}
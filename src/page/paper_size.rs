@@ -2,35 +2,122 @@ use std::fmt;
 
 use serde::{Deserialize, Serialize};
 
+use crate::error::Error;
+
+use super::length::Length;
+
 /// The size of the page.
 #[derive(Default, Clone, Deserialize, Serialize)]
 pub enum PaperSize {
+    /// US Letter: 8.5 × 11 in.
     #[default]
     Letter,
+    /// US Legal: 8.5 × 14 in.
     Legal,
+    /// US Executive: 7.25 × 10.5 in.
+    Executive,
+    /// Octavo: 6 × 9 in.
+    Octavo,
+    /// ISO A3: 297 × 420 mm.
+    A3,
+    /// ISO A4: 210 × 297 mm.
     A4,
+    /// ISO A5: 148 × 210 mm.
+    A5,
+    /// ISO A6: 105 × 148 mm.
+    A6,
+    /// ISO B4: 250 × 353 mm.
+    B4,
+    /// ISO B5: 176 × 250 mm.
+    B5,
+    /// ISO B6: 125 × 176 mm.
+    B6,
+    /// JIS B4: 257 × 364 mm.
+    JisB4,
+    /// JIS B5: 182 × 257 mm.
+    JisB5,
+    /// JIS B6: 128 × 182 mm.
+    JisB6,
+    /// A custom page size.
+    Custom { width: Length, height: Length },
 }
 
 impl PaperSize {
     /// The page width in pts.
-    pub const fn width(&self) -> f32 {
+    /// Returns an error if the width of a custom page is relative to the font size.
+    pub fn width(&self) -> Result<f32, Error> {
+        self.get_size().0.get_absolute_pts()
+    }
+
+    /// The page height in pts.
+    /// Returns an error if the height of a custom page is relative to the font size.
+    pub fn height(&self) -> Result<f32, Error> {
+        self.get_size().1.get_absolute_pts()
+    }
+
+    /// Returns the width and height of the page.
+    pub(super) fn get_size(&self) -> (Length, Length) {
+        let inches = |width: f32, height: f32| (Length::inches(width), Length::inches(height));
+        let mm = |width: f32, height: f32| (Length::mm(width), Length::mm(height));
         match self {
-            Self::Letter | Self::Legal => 614.295,
-            Self::A4 => 597.6729,
+            Self::Letter => inches(8.5, 11.),
+            Self::Legal => inches(8.5, 14.),
+            Self::Executive => inches(7.25, 10.5),
+            Self::Octavo => inches(6., 9.),
+            Self::A3 => mm(297., 420.),
+            Self::A4 => mm(210., 297.),
+            Self::A5 => mm(148., 210.),
+            Self::A6 => mm(105., 148.),
+            Self::B4 => mm(250., 353.),
+            Self::B5 => mm(176., 250.),
+            Self::B6 => mm(125., 176.),
+            Self::JisB4 => mm(257., 364.),
+            Self::JisB5 => mm(182., 257.),
+            Self::JisB6 => mm(128., 182.),
+            Self::Custom { width, height } => (width.clone(), height.clone()),
         }
     }
 }
 
 impl fmt::Display for PaperSize {
+    /// The page size as `geometry` options.
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "{}",
-            match self {
-                Self::A4 => "a4paper",
-                Self::Letter => "letterpaper",
-                Self::Legal => "legalpaper",
-            }
-        )
+        let (width, height) = self.get_size();
+        write!(f, "paperwidth={}, paperheight={}", width, height)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::page::Length;
+
+    use super::PaperSize;
+
+    #[test]
+    fn test_paper_size() {
+        assert_eq!(
+            PaperSize::Letter.width().unwrap(),
+            PaperSize::Legal.width().unwrap()
+        );
+        assert!(PaperSize::Legal.height().unwrap() > PaperSize::Letter.height().unwrap());
+        assert_eq!(PaperSize::Octavo.height().unwrap(), 9. * 72.27);
+        assert!(PaperSize::A5.width().unwrap() < PaperSize::A4.width().unwrap());
+        assert_eq!(
+            PaperSize::A4.to_string(),
+            "paperwidth=210mm, paperheight=297mm"
+        );
+        let custom = PaperSize::Custom {
+            width: Length::inches(5.5),
+            height: Length::inches(8.5),
+        };
+        assert_eq!(custom.width().unwrap(), 5.5 * 72.27);
+        assert_eq!(custom.to_string(), "paperwidth=5.5in, paperheight=8.5in");
+        // A page can't be relative to the font size.
+        let custom = PaperSize::Custom {
+            width: Length::em(40.),
+            height: Length::inches(8.5),
+        };
+        assert!(custom.width().is_err());
+        assert!(custom.height().is_ok());
     }
 }
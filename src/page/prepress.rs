@@ -0,0 +1,227 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{error::Error, font::font_metrics::FontMetrics, table::span_column::SpanColumn, tex};
+
+use super::{length::Length, paper_size::PaperSize, unit::Unit};
+
+/// The thickness of the crop marks in pts.
+const MARK_THICKNESS: f32 = 0.25;
+
+/// Crop marks, bleed, and a slug line for a print shop.
+///
+/// The page's paper size is the trim size. The PDF's pages are larger than the trim size:
+/// Around the trimmed page is the bleed, and around the bleed is the slug area, which has the crop marks and the slug line.
+/// The PDF's TrimBox and BleedBox are set to the trimmed page and the bleed.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct Prepress {
+    /// The width of the bleed around each edge of the trimmed page.
+    pub bleed: Length,
+    /// The width of the slug area around the bleed.
+    pub slug: Length,
+    /// If true, crop marks are drawn in the slug area at the corners of the trimmed page.
+    pub crop_marks: bool,
+    /// If not None, this is written in the slug area below the page, followed by the page number.
+    #[serde(default)]
+    pub job_name: Option<String>,
+}
+
+impl Prepress {
+    /// Returns the `geometry` options that set the size of the PDF's pages and the position of the trimmed page.
+    /// Returns an error if the paper size is relative to the font size.
+    pub(crate) fn get_geometry(
+        &self,
+        paper_size: &PaperSize,
+        font_metrics: &FontMetrics,
+    ) -> Result<String, Error> {
+        let size = self.get_size(paper_size, font_metrics)?;
+        Ok(format!(
+            "paperwidth={}pt, paperheight={}pt, layoutwidth={}pt, layoutheight={}pt, layouthoffset={}pt, layoutvoffset={}pt",
+            size.width,
+            size.height,
+            size.trim_width,
+            size.trim_height,
+            size.offset,
+            size.offset
+        ))
+    }
+
+    /// Returns the commands that draw the crop marks and the slug line and set the PDF boxes of each page.
+    /// These aren't added when measuring lines, only to the final PDF.
+    /// Returns an error if the paper size is relative to the font size.
+    pub(crate) fn get_commands(
+        &self,
+        paper_size: &PaperSize,
+        font_metrics: &FontMetrics,
+    ) -> Result<String, Error> {
+        let Size {
+            width,
+            height,
+            trim_width,
+            trim_height,
+            offset,
+            slug,
+        } = self.get_size(paper_size, font_metrics)?;
+        // The origin is the top-left corner of the page, and y is up, so y is negative.
+        let mut picture = vec![tex!("setlength", "\\unitlength", "1pt")];
+        if self.crop_marks {
+            let rule = |x: f32, y: f32, w: f32, h: f32| {
+                format!(
+                    "\\put({},{}){{{}}}",
+                    round(x),
+                    round(y),
+                    tex!("rule", format!("{}pt", round(w)), format!("{}pt", round(h)))
+                )
+            };
+            for y in [-offset, -(offset + trim_height)] {
+                picture.push(rule(0., y, slug, MARK_THICKNESS));
+                picture.push(rule(width - slug, y, slug, MARK_THICKNESS));
+            }
+            for x in [offset, offset + trim_width] {
+                picture.push(rule(x, -slug, MARK_THICKNESS, slug));
+                picture.push(rule(x, -height, MARK_THICKNESS, slug));
+            }
+        }
+        if let Some(job_name) = &self.job_name {
+            let mut job_name = job_name.clone();
+            SpanColumn::santitize_tex(&mut job_name);
+            picture.push(format!(
+                "\\put({},{}){{\\makebox(0,0)[l]{{\\normalfont\\tiny {}\\quad\\number\\value{{page}}}}}}",
+                round(offset),
+                round(-(height - slug / 2.)),
+                job_name
+            ));
+        }
+        // The PDF boxes are in PostScript points, and y is up from the bottom-left corner.
+        let bp = |pts: f32| round(pts / Unit::Bp.pts().unwrap()).to_string();
+        let pdf_box = |left: f32, bottom: f32, right: f32, top: f32| {
+            format!("[{} {} {} {}]", bp(left), bp(bottom), bp(right), bp(top))
+        };
+        picture.push(format!(
+            "\\special{{pdf:put @thispage <</TrimBox {} /BleedBox {}>>}}",
+            pdf_box(offset, offset, offset + trim_width, offset + trim_height),
+            pdf_box(slug, slug, width - slug, height - slug)
+        ));
+        Ok(tex!("AddToHook", "shipout/foreground", picture.join("\n")))
+    }
+
+    /// Returns the sizes of the page, the trimmed page, and the areas around it in pts.
+    /// Returns an error if the paper size is relative to the font size.
+    fn get_size(&self, paper_size: &PaperSize, font_metrics: &FontMetrics) -> Result<Size, Error> {
+        let trim_width = paper_size.width()?;
+        let trim_height = paper_size.height()?;
+        let slug = self.slug.get_pts(font_metrics);
+        let offset = self.bleed.get_pts(font_metrics) + slug;
+        Ok(Size {
+            width: trim_width + 2. * offset,
+            height: trim_height + 2. * offset,
+            trim_width,
+            trim_height,
+            offset,
+            slug,
+        })
+    }
+}
+
+impl Default for Prepress {
+    fn default() -> Self {
+        Self {
+            bleed: Length::inches(0.125),
+            slug: Length::inches(0.5),
+            crop_marks: true,
+            job_name: None,
+        }
+    }
+}
+
+/// Sizes in pts.
+struct Size {
+    /// The width of the PDF's page.
+    width: f32,
+    /// The height of the PDF's page.
+    height: f32,
+    /// The width of the trimmed page.
+    trim_width: f32,
+    /// The height of the trimmed page.
+    trim_height: f32,
+    /// The distance from the edges of the PDF's page to the trimmed page.
+    offset: f32,
+    /// The width of the slug area.
+    slug: f32,
+}
+
+/// Round to two decimal places.
+fn round(value: f32) -> f32 {
+    (value * 100.).round() / 100.
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        font::font_metrics::FontMetrics,
+        page::{Length, PaperSize},
+    };
+
+    use super::Prepress;
+
+    #[test]
+    fn test_prepress() {
+        let paper_size = PaperSize::Custom {
+            width: Length::pt(100.),
+            height: Length::pt(200.),
+        };
+        let font_metrics = FontMetrics::default();
+        let prepress = Prepress {
+            bleed: Length::pt(10.),
+            slug: Length::pt(20.),
+            crop_marks: true,
+            job_name: Some("Berakhot".to_string()),
+        };
+        assert_eq!(
+            prepress.get_geometry(&paper_size, &font_metrics).unwrap(),
+            "paperwidth=160pt, paperheight=260pt, layoutwidth=100pt, layoutheight=200pt, layouthoffset=30pt, layoutvoffset=30pt"
+        );
+        let commands = prepress.get_commands(&paper_size, &font_metrics).unwrap();
+        assert!(commands
+            .starts_with("\\AddToHook{shipout/foreground}{\\setlength{\\unitlength}{1pt}\n"));
+        for mark in [
+            "\\put(0,-30){\\rule{20pt}{0.25pt}}",
+            "\\put(140,-230){\\rule{20pt}{0.25pt}}",
+            "\\put(30,-20){\\rule{0.25pt}{20pt}}",
+            "\\put(130,-260){\\rule{0.25pt}{20pt}}",
+        ] {
+            assert!(commands.contains(mark));
+        }
+        assert!(commands.contains(
+            "\\put(30,-250){\\makebox(0,0)[l]{\\normalfont\\tiny Berakhot\\quad\\number\\value{page}}}"
+        ));
+        assert!(commands.contains(
+            "\\special{pdf:put @thispage <</TrimBox [29.89 29.89 129.51 229.14] /BleedBox [19.93 19.93 139.48 239.1]>>}}"
+        ));
+
+        // No crop marks or slug line.
+        let prepress = Prepress {
+            crop_marks: false,
+            ..Default::default()
+        };
+        let commands = prepress.get_commands(&paper_size, &font_metrics).unwrap();
+        assert!(!commands.contains("\\rule"));
+        assert!(!commands.contains("\\makebox"));
+        assert!(commands.contains("/TrimBox"));
+
+        // The job name is sanitized.
+        let prepress = Prepress {
+            job_name: Some("Bava_Kamma 50%".to_string()),
+            ..Default::default()
+        };
+        let commands = prepress.get_commands(&paper_size, &font_metrics).unwrap();
+        assert!(commands.contains("\\tiny Bava\\_Kamma 50\\%\\quad"));
+
+        // The paper size can't be relative to the font size.
+        let paper_size = PaperSize::Custom {
+            width: Length::pt(100.),
+            height: Length::em(20.),
+        };
+        assert!(prepress.get_geometry(&paper_size, &font_metrics).is_err());
+        assert!(prepress.get_commands(&paper_size, &font_metrics).is_err());
+    }
+}
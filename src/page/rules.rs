@@ -0,0 +1,261 @@
+use serde::{Deserialize, Serialize};
+
+use crate::tex;
+
+use super::length::Length;
+
+/// The style of the column rules.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, Eq, PartialEq)]
+pub enum RuleStyle {
+    #[default]
+    Solid,
+    /// Dashes that are 3pt long and 2pt apart.
+    Dashed,
+    /// Square dots as wide as the rule, 2pt apart.
+    Dotted,
+}
+
+/// Vertical rules between columns, and optional horizontal rules between tables.
+///
+/// Solid rules are drawn by paracol in the column separation, so they don't change the width of the columns.
+/// paracol can only draw solid rules, so dashed and dotted rules are drawn with TikZ between the tops and bottoms of the columns of each table.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct ColumnRules {
+    /// The width of each rule.
+    pub width: Length,
+    /// The style of the rules.
+    #[serde(default)]
+    pub style: RuleStyle,
+    /// An optional color. This is either an HTML color, e.g. `#808080`, or an xcolor expression, e.g. `gray!50`.
+    /// If None, the rules are black.
+    #[serde(default)]
+    pub color: Option<String>,
+    /// If true, there is a horizontal rule across the page between each table.
+    #[serde(default)]
+    pub rows: bool,
+}
+
+impl ColumnRules {
+    /// The name of the color of the rules.
+    const COLOR: &str = "talmudcolumnrule";
+    /// The command that starts a table whose columns are marked. See [`ColumnRules::mark_column`].
+    pub(crate) const BEGIN_TABLE: &str = "\\stepcounter{talmudtable}";
+    /// The TikZ macros that mark the columns and draw the rules between them.
+    /// Each mark is at the left edge of its column, so a rule is half of the column separation to the left of the rightmost of two columns, even if paracol swapped them.
+    const TIKZ: &str = r"\usepackage{tikz}
+\usetikzlibrary{calc}
+\newcounter{talmudtable}
+\globalcounter{talmudtable}
+\newcommand{\talmudcolumntop}[1]{\tikz[remember picture, overlay]\coordinate (talmud-\thetalmudtable-#1-top) at (0, \the\ht\strutbox);}
+\newcommand{\talmudcolumnbottom}[1]{\tikz[remember picture, overlay]\coordinate (talmud-\thetalmudtable-#1-bottom) at (0, -\the\dp\strutbox);}
+\newcommand{\talmudcolumnrule}[2]{\tikz[remember picture, overlay]\draw[talmudrule] let \p1 = (talmud-\thetalmudtable-#1-top), \p2 = (talmud-\thetalmudtable-#2-top), \p3 = (talmud-\thetalmudtable-#1-bottom), \p4 = (talmud-\thetalmudtable-#2-bottom) in ({max(\x1, \x2) - 0.5 * \columnsep}, {max(\y1, \y2)}) -- ({max(\x1, \x2) - 0.5 * \columnsep}, {min(\y3, \y4)});}";
+    /// The gap between dashes or dots, in pts.
+    const GAP: f32 = 2.;
+    /// The length of a dash, in pts.
+    const DASH: f32 = 3.;
+
+    /// Returns the preamble declarations.
+    pub(crate) fn get_preamble(&self) -> String {
+        let mut preamble = vec![];
+        if let Some(color) = &self.color {
+            preamble.push(tex!("usepackage", "xcolor"));
+            preamble.push(match color.strip_prefix('#') {
+                Some(html) => tex!("definecolor", Self::COLOR, "HTML", html.to_uppercase()),
+                None => tex!("colorlet", Self::COLOR, color),
+            });
+        }
+        if self.is_tikz() {
+            preamble.push(Self::TIKZ.to_string());
+            let (on, off) = self.get_dash_pattern();
+            let mut style = format!(
+                "line width={}, dash pattern=on {} off {}",
+                self.width, on, off
+            );
+            if self.color.is_some() {
+                style.push_str(", draw=");
+                style.push_str(Self::COLOR);
+            }
+            preamble.push(tex!("tikzset", format!("talmudrule/.style={{{}}}", style)));
+        }
+        preamble.join("\n")
+    }
+
+    /// Returns the commands that draw the rules.
+    /// The rules aren't drawn when measuring lines, only in the final PDF.
+    pub(crate) fn get_commands(&self) -> String {
+        // Dashed and dotted rules are drawn with TikZ instead.
+        if self.is_tikz() {
+            return String::default();
+        }
+        let mut commands = tex!("setlength", "\\columnseprule", &self.width);
+        if self.color.is_some() {
+            commands += &tex!("colseprulecolor", Self::COLOR);
+        }
+        commands
+    }
+
+    /// Returns true if the rules are dashed or dotted, and drawn with TikZ instead of by paracol.
+    pub(crate) fn is_tikz(&self) -> bool {
+        self.style != RuleStyle::Solid
+    }
+
+    /// Marks the top and bottom of the TeX of the column with index `index` in a table that starts with [`ColumnRules::BEGIN_TABLE`].
+    pub(crate) fn mark_column(text: &str, index: usize) -> String {
+        format!(
+            "{}{}{}",
+            tex!("talmudcolumntop", index),
+            text,
+            tex!("talmudcolumnbottom", index)
+        )
+    }
+
+    /// Returns the TeX that draws a rule between the marked columns with indices `left` and `right`.
+    pub(crate) fn draw_rule(left: usize, right: usize) -> String {
+        tex!("talmudcolumnrule", left, right)
+    }
+
+    /// Returns the horizontal rule between tables, if any.
+    pub(crate) fn get_row_rule(&self) -> Option<String> {
+        if !self.rows {
+            return None;
+        }
+        let rule = match self.style {
+            RuleStyle::Solid => tex!("rule", "\\linewidth", &self.width),
+            _ => {
+                let (on, off) = self.get_dash_pattern();
+                format!(
+                    "\\hbox to\\linewidth{{\\xleaders\\hbox to\\dimexpr {} + {}\\relax{{\\hss{}\\hss}}\\hfill}}",
+                    on,
+                    off,
+                    tex!("rule", on, &self.width)
+                )
+            }
+        };
+        Some(match &self.color {
+            Some(_) => format!(
+                "\\par\\noindent{{{}{}}}\\par",
+                tex!("color", Self::COLOR),
+                rule
+            ),
+            None => format!("\\par\\noindent{}\\par", rule),
+        })
+    }
+
+    /// Returns the length of each dash or dot, and the gap between them.
+    fn get_dash_pattern(&self) -> (Length, Length) {
+        let on = match self.style {
+            RuleStyle::Dotted => self.width.clone(),
+            _ => Length::pt(Self::DASH),
+        };
+        (on, Length::pt(Self::GAP))
+    }
+}
+
+impl Default for ColumnRules {
+    fn default() -> Self {
+        Self {
+            width: Length::pt(0.4),
+            style: RuleStyle::default(),
+            color: None,
+            rows: false,
+        }
+    }
+}
+
+/// An ornament between the opening block and the body of the page.
+#[derive(Clone, Deserialize, Serialize)]
+pub enum Ornament {
+    /// A horizontal rule across the page. This is the thickness of the rule.
+    Rule(Length),
+    /// Centered text, e.g. `❧`, set in the center column's font. The text is TeX.
+    Text(String),
+}
+
+impl Ornament {
+    /// Returns the TeX of the ornament. `font` is the command that sets the center column's font.
+    pub(crate) fn to_tex(&self, font: &str) -> String {
+        match self {
+            Self::Rule(thickness) => format!(
+                "\\par\\noindent{}\\par",
+                tex!("rule", "\\linewidth", thickness)
+            ),
+            Self::Text(text) => format!("\\par{{\\centering{} {}\\par}}", font, text),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::from_str;
+
+    use crate::page::Length;
+
+    use super::{ColumnRules, Ornament, RuleStyle};
+
+    #[test]
+    fn test_rules() {
+        let rules = ColumnRules::default();
+        assert!(rules.get_preamble().is_empty());
+        assert_eq!(rules.get_commands(), "\\setlength{\\columnseprule}{0.4pt}");
+        assert_eq!(rules.get_row_rule(), None);
+        let rules = ColumnRules {
+            width: Length::pt(1.),
+            color: Some("#80a0c0".to_string()),
+            rows: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            rules.get_preamble(),
+            "\\usepackage{xcolor}\n\\definecolor{talmudcolumnrule}{HTML}{80A0C0}"
+        );
+        assert_eq!(
+            rules.get_commands(),
+            "\\setlength{\\columnseprule}{1pt}\\colseprulecolor{talmudcolumnrule}"
+        );
+        assert_eq!(
+            rules.get_row_rule().unwrap(),
+            "\\par\\noindent{\\color{talmudcolumnrule}\\rule{\\linewidth}{1pt}}\\par"
+        );
+        let rules = ColumnRules {
+            width: Length::pt(1.),
+            color: Some("gray!50".to_string()),
+            ..Default::default()
+        };
+        assert!(rules
+            .get_preamble()
+            .ends_with("\\colorlet{talmudcolumnrule}{gray!50}"));
+        // Dashed and dotted rules are drawn with TikZ instead of by paracol.
+        let rules = from_str::<ColumnRules>(
+            r#"{"width": {"length": 0.4, "unit": "Pt"}, "style": "Dashed", "rows": true}"#,
+        )
+        .unwrap();
+        assert_eq!(rules.style, RuleStyle::Dashed);
+        assert!(rules.get_commands().is_empty());
+        assert!(rules.get_preamble().ends_with(
+            "\\tikzset{talmudrule/.style={line width=0.4pt, dash pattern=on 3pt off 2pt}}"
+        ));
+        assert_eq!(
+            rules.get_row_rule().unwrap(),
+            "\\par\\noindent\\hbox to\\linewidth{\\xleaders\\hbox to\\dimexpr 3pt + 2pt\\relax{\\hss\\rule{3pt}{0.4pt}\\hss}\\hfill}\\par"
+        );
+        assert_eq!(
+            ColumnRules::mark_column("text", 1),
+            "\\talmudcolumntop{1}text\\talmudcolumnbottom{1}"
+        );
+        let rules = ColumnRules {
+            style: RuleStyle::Dotted,
+            ..Default::default()
+        };
+        assert!(rules
+            .get_preamble()
+            .contains("dash pattern=on 0.4pt off 2pt"));
+        assert_eq!(
+            Ornament::Rule(Length::pt(0.5)).to_tex("\\centerfont"),
+            "\\par\\noindent\\rule{\\linewidth}{0.5pt}\\par"
+        );
+        assert_eq!(
+            Ornament::Text("❧".to_string()).to_tex("\\centerfont"),
+            "\\par{\\centering\\centerfont ❧\\par}"
+        );
+    }
+}
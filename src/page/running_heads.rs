@@ -0,0 +1,276 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{table::span_column::SpanColumn, tex};
+
+use super::numbering::Numbering;
+
+/// The side of a folio. Each folio has two sides: a (the front) and b (the back).
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, Eq, PartialEq)]
+pub enum Side {
+    #[default]
+    A,
+    B,
+}
+
+/// The left, center, and right text of a header or footer.
+///
+/// Each text is a template that can include placeholders:
+///
+/// - `{tractate}` is the name of the tractate.
+/// - `{chapter}` is the name of the chapter.
+/// - `{folio}` is the folio number of the page.
+/// - `{side}` is the side of the folio: a or b.
+/// - `{daf}` is the folio and its side, e.g. 2a.
+/// - `{page}` is the page number.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct RunningHead {
+    #[serde(default)]
+    pub left: String,
+    #[serde(default)]
+    pub center: String,
+    #[serde(default)]
+    pub right: String,
+}
+
+/// Running headers and footers.
+///
+/// The folio and side advance with each page: 2a, 2b, 3a, etc.
+/// Running headers and footers aren't included when measuring lines, only in the final PDF.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct RunningHeads {
+    /// The name of the tractate, e.g. Berakhot.
+    pub tractate: String,
+    /// The name of the chapter.
+    pub chapter: String,
+    /// The folio number of the first page.
+    pub folio: u32,
+    /// The side of the folio of the first page.
+    pub side: Side,
+    /// How folios are numbered.
+    #[serde(default)]
+    pub folio_numbering: Numbering,
+    /// How pages are numbered.
+    #[serde(default)]
+    pub page_numbering: Numbering,
+    /// The header.
+    #[serde(default)]
+    pub header: RunningHead,
+    /// The footer.
+    #[serde(default)]
+    pub footer: RunningHead,
+}
+
+impl RunningHeads {
+    /// The command that shows the running headers and footers.
+    pub(crate) const PAGE_STYLE: &str = "\\pagestyle{scrheadings}";
+
+    /// Returns the preamble declarations. `font` is the command that sets the font of the headers and footers.
+    /// `first_page` is the page number of the first page.
+    ///
+    /// The headers and footers are declared but not shown. To show them, add [`Self::PAGE_STYLE`] to the document.
+    pub(crate) fn get_preamble(&self, font: &str, first_page: u32) -> String {
+        let side = match self.side {
+            Side::A => 0,
+            Side::B => 1,
+        };
+        let first_page = first_page as i64;
+        let mut preamble = format!(
+            "{}\n\\clearpairofpagestyles\n\\pagestyle{{empty}}\n{}",
+            tex!("usepackage", "scrlayer-scrpage"),
+            tex!("setkomafont", "pageheadfoot", font)
+        );
+        // The folio of the page. \numexpr rounds, so this is the folio plus floor((page - first_page + side) / 2).
+        preamble += &format!(
+            "\n\\newcommand{{\\talmudfolionumber}}{{\\the\\numexpr {} + (\\value{{page}} + {}) / 2\\relax}}",
+            self.folio as i64 - 1,
+            side + 1 - first_page
+        );
+        let numbering = self.folio_numbering;
+        preamble += &format!(
+            "\n\\newcommand{{\\talmudfolionumeral}}{{{}}}",
+            numbering.get_tex("\\talmudfolionumber", "\\talmudfolionumber")
+        );
+        // The side of the page.
+        preamble += &format!(
+            "\n\\newcommand{{\\talmudsidenumeral}}{{\\ifodd\\numexpr\\value{{page}} - {} + {}\\relax {}\\else {}\\fi}}",
+            first_page,
+            side,
+            numbering.format_side(Side::B),
+            numbering.format_side(Side::A)
+        );
+        // Hebrew numerals are typeset right to left.
+        for (command, tex) in [
+            ("talmudfolio", "\\talmudfolionumeral".to_string()),
+            ("talmudside", "\\talmudsidenumeral".to_string()),
+            (
+                "talmuddaf",
+                format!(
+                    "\\talmudfolionumeral{}\\talmudsidenumeral",
+                    numbering.get_side_separator().replace(' ', "\\ ")
+                ),
+            ),
+        ] {
+            preamble += &format!(
+                "\n\\newcommand{{\\{}}}{{{}}}",
+                command,
+                numbering.wrap(&tex)
+            );
+        }
+        // The page number.
+        if self.page_numbering != Numbering::Arabic {
+            preamble += &format!(
+                "\n\\renewcommand{{\\thepage}}{{{}}}",
+                self.page_numbering.wrap(
+                    &self
+                        .page_numbering
+                        .get_tex("\\value{page}", "\\arabic{page}")
+                )
+            );
+        }
+        for (head, commands) in [
+            (
+                &self.header,
+                [
+                    ["lehead", "lohead"],
+                    ["cehead", "cohead"],
+                    ["rehead", "rohead"],
+                ],
+            ),
+            (
+                &self.footer,
+                [
+                    ["lefoot", "lofoot"],
+                    ["cefoot", "cofoot"],
+                    ["refoot", "rofoot"],
+                ],
+            ),
+        ] {
+            for (template, commands) in [&head.left, &head.center, &head.right]
+                .into_iter()
+                .zip(commands)
+            {
+                if template.is_empty() {
+                    continue;
+                }
+                let text = self.get_text(template);
+                preamble.push('\n');
+                // Set the text on even and odd pages.
+                for command in commands {
+                    preamble += &tex!(command, &text);
+                }
+            }
+        }
+        preamble
+    }
+
+    /// Returns true if the numerals are Hebrew. Hebrew numerals require polyglossia's Hebrew support.
+    pub(crate) fn requires_hebrew(&self) -> bool {
+        self.folio_numbering.is_hebrew() || self.page_numbering.is_hebrew()
+    }
+
+    /// Replace the placeholders in `template`. The tractate and chapter are sanitized.
+    fn get_text(&self, template: &str) -> String {
+        let [mut tractate, mut chapter] = [self.tractate.clone(), self.chapter.clone()];
+        SpanColumn::santitize_tex(&mut tractate);
+        SpanColumn::santitize_tex(&mut chapter);
+        template
+            .replace("{tractate}", &tractate)
+            .replace("{chapter}", &chapter)
+            .replace("{folio}", "{\\talmudfolio}")
+            .replace("{side}", "{\\talmudside}")
+            .replace("{daf}", "{\\talmuddaf}")
+            .replace("{page}", "{\\thepage}")
+    }
+}
+
+impl Default for RunningHeads {
+    fn default() -> Self {
+        Self {
+            tractate: String::default(),
+            chapter: String::default(),
+            folio: 2,
+            side: Side::A,
+            folio_numbering: Numbering::default(),
+            page_numbering: Numbering::default(),
+            header: RunningHead {
+                left: "{chapter}".to_string(),
+                center: "{tractate}".to_string(),
+                right: "{folio}{side}".to_string(),
+            },
+            footer: RunningHead::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::page::Numbering;
+
+    use super::{RunningHead, RunningHeads, Side};
+
+    #[test]
+    fn test_running_heads() {
+        let running_heads = RunningHeads {
+            tractate: "Berakhot".to_string(),
+            chapter: "Me'eimatai".to_string(),
+            folio: 2,
+            side: Side::B,
+            footer: RunningHead {
+                center: "{page}".to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let preamble = running_heads.get_preamble("\\centerfont", 1);
+        assert!(preamble.contains("\\setkomafont{pageheadfoot}{\\centerfont}"));
+        assert!(preamble.contains("\\lehead{Me'eimatai}\\lohead{Me'eimatai}"));
+        assert!(preamble.contains("\\cehead{Berakhot}\\cohead{Berakhot}"));
+        assert!(preamble.contains("\\rehead{{\\talmudfolio}{\\talmudside}}"));
+        assert!(preamble.contains("\\cefoot{{\\thepage}}"));
+        assert!(!preamble.contains("lefoot"));
+        // The first page is 2b.
+        assert!(preamble.contains("{\\the\\numexpr 1 + (\\value{page} + 1) / 2\\relax}"));
+        assert!(preamble.contains("\\ifodd\\numexpr\\value{page} - 1 + 1\\relax b\\else a\\fi"));
+        assert!(preamble.contains("\\newcommand{\\talmudfolio}{{\\talmudfolionumeral}}"));
+        assert!(!preamble.contains("\\thepage}{"));
+
+        // Hebrew folios and Roman page numbers.
+        let running_heads = RunningHeads {
+            folio_numbering: Numbering::Hebrew,
+            page_numbering: Numbering::Roman,
+            header: RunningHead {
+                center: "{daf}".to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert!(running_heads.requires_hebrew());
+        let preamble = running_heads.get_preamble("\\centerfont", 1);
+        assert!(preamble.contains(
+            "\\newcommand{\\talmudfolionumeral}{\\Hebrewnumeral{\\number\\numexpr \\talmudfolionumber\\relax}}"
+        ));
+        assert!(preamble.contains("\\relax ע״ב\\else ע״א\\fi"));
+        assert!(preamble.contains(
+            "\\newcommand{\\talmuddaf}{\\texthebrew{\\talmudfolionumeral\\ \\talmudsidenumeral}}"
+        ));
+        assert!(preamble
+            .contains("\\renewcommand{\\thepage}{{\\romannumeral\\numexpr \\value{page}\\relax}}"));
+        assert!(preamble.contains("\\cehead{{\\talmuddaf}}"));
+        assert!(!preamble.contains("\\ifcase"));
+
+        // The first page is page 2.
+        let preamble = RunningHeads::default().get_preamble("\\centerfont", 2);
+        assert!(preamble.contains("{\\the\\numexpr 1 + (\\value{page} + -1) / 2\\relax}"));
+        assert!(preamble.contains("\\ifodd\\numexpr\\value{page} - 2 + 0\\relax b\\else a\\fi"));
+
+        // The tractate and chapter are sanitized.
+        let running_heads = RunningHeads {
+            tractate: "Bava_Kamma #1".to_string(),
+            chapter: "50% & more".to_string(),
+            ..Default::default()
+        };
+        let preamble = running_heads.get_preamble("\\centerfont", 1);
+        assert!(preamble.contains("\\cehead{Bava\\_Kamma \\#1}"));
+        assert!(preamble.contains("\\lehead{50\\% \\& more}"));
+    }
+}
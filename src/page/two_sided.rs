@@ -0,0 +1,129 @@
+use serde::{Deserialize, Serialize};
+
+use crate::tex;
+
+/// The parity of a page. Recto pages are odd and have the binding on the left. Verso pages are even and have the binding on the right.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, Eq, PartialEq)]
+pub enum Parity {
+    #[default]
+    Recto,
+    Verso,
+}
+
+/// One of the commentaries of the source text.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, Eq, PartialEq)]
+pub enum Commentary {
+    /// The left column of the source text.
+    #[default]
+    Left,
+    /// The right column of the source text.
+    Right,
+}
+
+/// Two-sided printing.
+///
+/// One commentary is always on the inner (binding) edge of the page and the other is always on the outer edge,
+/// so the commentaries swap sides between recto and verso pages.
+///
+/// The document class is always two-sided, so marginalia are always in the outer margin,
+/// and the page's left and right margins are the inner and outer margins.
+#[derive(Clone, Default, Deserialize, Serialize)]
+pub struct TwoSided {
+    /// The commentary that is on the inner edge of the page. The other commentary is on the outer edge.
+    #[serde(default)]
+    pub inner: Commentary,
+    /// The parity of the first page.
+    #[serde(default)]
+    pub first_page: Parity,
+}
+
+impl TwoSided {
+    /// The preamble declaration. paracol swaps the columns on even pages.
+    ///
+    /// Marginalia are set with `\marginnote`, not `\marginpar`, so paracol doesn't move them.
+    /// `\marginnote` puts them in the outer margin because the document class is two-sided; see [`Self::get_class_options`].
+    pub(crate) const PREAMBLE: &str = "\\twosided[c]";
+
+    /// Returns the page number of the first page.
+    pub(crate) const fn first_page_number(&self) -> u32 {
+        match self.first_page {
+            Parity::Recto => 1,
+            Parity::Verso => 2,
+        }
+    }
+
+    /// Returns true if the source text's left and right commentaries must be swapped.
+    /// On recto pages, the inner edge is on the left.
+    pub(crate) const fn swap_commentaries(&self) -> bool {
+        matches!(self.inner, Commentary::Right)
+    }
+
+    /// Returns the document class options with `twoside` instead of `oneside`.
+    pub(crate) fn get_class_options(class_options: &[String]) -> Vec<String> {
+        let mut class_options = class_options
+            .iter()
+            .filter(|option| option.as_str() != "oneside")
+            .cloned()
+            .collect::<Vec<String>>();
+        if !class_options.iter().any(|option| option == "twoside") {
+            class_options.push("twoside".to_string());
+        }
+        class_options
+    }
+
+    /// Swap the `left` and `right` commentaries if the right commentary is on the inner edge.
+    /// On recto pages, the inner edge is on the left.
+    pub(crate) fn arrange<T>(&self, left: &mut T, right: &mut T) {
+        if self.swap_commentaries() {
+            std::mem::swap(left, right);
+        }
+    }
+
+    /// Returns the commands that set the parity of the first page.
+    pub(crate) fn get_commands(&self) -> String {
+        match self.first_page {
+            Parity::Recto => String::default(),
+            Parity::Verso => format!("{}\n", tex!("setcounter", "page", 2)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Commentary, Parity, TwoSided};
+
+    #[test]
+    fn test_two_sided() {
+        let two_sided = TwoSided::default();
+        assert!(!two_sided.swap_commentaries());
+        assert_eq!(two_sided.first_page_number(), 1);
+        assert!(two_sided.get_commands().is_empty());
+        let two_sided = TwoSided {
+            inner: Commentary::Right,
+            first_page: Parity::Verso,
+        };
+        assert!(two_sided.swap_commentaries());
+        assert_eq!(two_sided.first_page_number(), 2);
+        assert_eq!(two_sided.get_commands(), "\\setcounter{page}{2}\n");
+
+        // The right commentary is on the left of recto pages.
+        let (mut left, mut right) = ("left", "right");
+        two_sided.arrange(&mut left, &mut right);
+        assert_eq!((left, right), ("right", "left"));
+        TwoSided::default().arrange(&mut left, &mut right);
+        assert_eq!((left, right), ("right", "left"));
+
+        // The document class is always two-sided.
+        assert_eq!(
+            TwoSided::get_class_options(&["11pt".to_string(), "oneside".to_string()]),
+            vec!["11pt".to_string(), "twoside".to_string()]
+        );
+        assert_eq!(
+            TwoSided::get_class_options(&["twoside".to_string()]),
+            vec!["twoside".to_string()]
+        );
+
+        // paracol only swaps the columns.
+        assert_eq!(TwoSided::PREAMBLE, "\\twosided[c]");
+    }
+}
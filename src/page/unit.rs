@@ -2,14 +2,51 @@ use std::fmt;
 
 use serde::{Deserialize, Serialize};
 
+/// TeX points per inch.
+const PTS_IN: f32 = 72.27;
+/// The x-height of a font, relative to its size. This is an approximation; the actual x-height depends on the font.
+pub(super) const EX_PER_EM: f32 = 0.5;
+
 /// LaTeX units.
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub enum Unit {
+    /// TeX points.
     Pt,
     Mm,
     Cm,
     In,
+    /// Big (PostScript) points. There are 72 per inch.
+    Bp,
+    /// Picas. There are 12 points per pica.
+    Pc,
+    /// Didot points.
+    Dd,
+    /// Ciceros. There are 12 Didot points per cicero.
+    Cc,
+    /// Scaled points. There are 65536 per point.
+    Sp,
+    /// The font size.
     Em,
+    /// The x-height of the font.
+    Ex,
+}
+
+impl Unit {
+    /// Returns the number of pts per unit, or None if the unit is relative to the font size.
+    pub const fn pts(&self) -> Option<f32> {
+        match self {
+            Self::Pt => Some(1.),
+            Self::Mm => Some(PTS_IN / 25.4),
+            Self::Cm => Some(PTS_IN / 2.54),
+            Self::In => Some(PTS_IN),
+            Self::Bp => Some(PTS_IN / 72.),
+            Self::Pc => Some(12.),
+            Self::Dd => Some(1238. / 1157.),
+            Self::Cc => Some(12. * 1238. / 1157.),
+            Self::Sp => Some(1. / 65536.),
+            Self::Em | Self::Ex => None,
+        }
+    }
 }
 
 impl fmt::Display for Unit {
@@ -22,7 +59,13 @@ impl fmt::Display for Unit {
                 Self::Mm => "mm",
                 Self::Cm => "cm",
                 Self::In => "in",
+                Self::Bp => "bp",
+                Self::Pc => "pc",
+                Self::Dd => "dd",
+                Self::Cc => "cc",
+                Self::Sp => "sp",
                 Self::Em => "em",
+                Self::Ex => "ex",
             }
         )
     }
@@ -1,7 +1,22 @@
 pub use crate::{
     error::Error,
-    font::{font_metrics::FontMetrics, fonts::Fonts, Font},
-    page::{Length, Margins, Page, PaperSize, Unit},
-    text::{Daf, SourceText},
+    font::{
+        column_font::ColumnFont,
+        face::{Face, Faces},
+        fallback::{CharRange, Fallback, Script},
+        font_metrics::FontMetrics,
+        fonts::Fonts,
+        glyph_coverage::MissingGlyph,
+        styled_font::{FontStyle, StyledFont},
+        Font,
+    },
+    page::{
+        ColumnRules, Commentary, Document, Hyphenation, Length, Margins, Metadata, Numbering,
+        Ornament, Package, Page, PaperSize, Parity, Prepress, RuleStyle, RunningHead, RunningHeads,
+        Side, TwoSided, Unit,
+    },
+    span::language::Language,
+    table::position::Position,
+    text::{Cartouche, Daf, Frame, SourceText, Title},
     Talmudifier,
 };
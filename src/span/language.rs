@@ -0,0 +1,85 @@
+use serde::{Deserialize, Serialize};
+
+use crate::error::Error;
+
+/// The language of a word. This sets the hyphenation patterns, font, and direction.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Deserialize, Serialize)]
+pub enum Language {
+    English,
+    Hebrew,
+    /// Aramaic is written in Hebrew script, so it's typeset with the Hebrew hyphenation patterns and direction.
+    Aramaic,
+    Latin,
+}
+
+impl Language {
+    /// Parse a language tag, e.g. `"he"`.
+    pub fn from_tag(tag: &str) -> Result<Self, Error> {
+        match tag.to_lowercase().as_str() {
+            "en" | "en-us" | "en-gb" | "eng" => Ok(Self::English),
+            "he" | "heb" => Ok(Self::Hebrew),
+            "arc" => Ok(Self::Aramaic),
+            "la" | "lat" => Ok(Self::Latin),
+            _ => Err(Error::Language(tag.to_string())),
+        }
+    }
+
+    /// The name of the language in polyglossia.
+    pub const fn polyglossia(&self) -> &'static str {
+        match self {
+            Self::English => "english",
+            Self::Hebrew | Self::Aramaic => "hebrew",
+            Self::Latin => "latin",
+        }
+    }
+
+    /// The name of the language's font family command, without the backslash.
+    pub const fn font_command(&self) -> &'static str {
+        match self {
+            Self::English => "englishfont",
+            Self::Hebrew => "hebrewfont",
+            Self::Aramaic => "aramaicfont",
+            Self::Latin => "latinfont",
+        }
+    }
+
+    /// Returns the command that starts a span of text in this language.
+    ///
+    /// If `font` is Some, the language doesn't have its own font and the text is set in `font`.
+    /// Otherwise, polyglossia would set it in the document's main font.
+    pub fn get_command(&self, font: Option<&str>) -> String {
+        let command = format!("\\text{}{{", self.polyglossia());
+        match (self, font) {
+            (_, Some(font)) => format!("{} ", command + font),
+            // Polyglossia doesn't switch to \aramaicfont automatically.
+            (Self::Aramaic, None) => format!("{}\\{} ", command, self.font_command()),
+            _ => command,
+        }
+    }
+
+    /// Returns true if the language is written right-to-left.
+    pub const fn is_rtl(&self) -> bool {
+        matches!(self, Self::Hebrew | Self::Aramaic)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Language;
+
+    #[test]
+    fn test_command() {
+        assert_eq!(Language::from_tag("he").unwrap(), Language::Hebrew);
+        assert!(Language::from_tag("xx").is_err());
+        assert_eq!(Language::Hebrew.get_command(None), "\\texthebrew{");
+        assert_eq!(
+            Language::Aramaic.get_command(None),
+            "\\texthebrew{\\aramaicfont "
+        );
+        // A language without its own font is set in the given font.
+        assert_eq!(
+            Language::Aramaic.get_command(Some("\\leftfont")),
+            "\\texthebrew{\\leftfont "
+        );
+    }
+}
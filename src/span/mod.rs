@@ -1,104 +1,277 @@
 //! A `Span` is a vec of `Word`s and some associated functions.
 //!
+use language::Language;
+use lazy_static::lazy_static;
 use markdown::{mdast::Node, to_mdast, Constructs, ParseOptions};
 use position::Position;
+use raw_tex::RawTex;
+use regex::Regex;
 use style::Style;
 use word::Word;
 
 use crate::error::Error;
 
+pub mod language;
 pub mod position;
+pub mod raw_tex;
 pub mod style;
 mod word;
 
-type LatexCommand = (Option<&'static str>, Option<&'static str>);
+lazy_static! {
+    static ref RE_INLINE_TEX: Regex = Regex::new(r#"(?s)^tex(\[(.*?)\])?:(.*)$"#).unwrap();
+    static ref RE_SPAN_LANG: Regex =
+        Regex::new(r#"^<span\s+lang\s*=\s*["']?([\w-]+)["']?\s*>$"#).unwrap();
+}
+
+/// The prefix and suffix of a TeX command.
+type LatexCommand = (Option<String>, Option<String>);
+
+/// A non-breaking space. Words joined by this character are treated as a single word.
+pub const NBSP: char = '\u{00A0}';
+/// Unicode spaces that don't break a line.
+const NON_BREAKING_SPACES: [char; 3] = [NBSP, '\u{2007}', '\u{202F}'];
 
 /// A vec of words derived from a Markdown string.
 pub struct Span(pub Vec<Word>);
 
 impl Span {
     /// Parse raw markdown text and get a vec of words.
-    pub fn from_md(md: &str) -> Result<Self, Error> {
-        let parse_options = ParseOptions {
-            constructs: Constructs::gfm(),
-            ..Default::default()
-        };
-        match to_mdast(md, &parse_options) {
+    /// If `raw_tex` is true, `tex` code is emitted verbatim rather than being treated as marginalia.
+    pub fn from_md(md: &str, raw_tex: bool) -> Result<Self, Error> {
+        match to_mdast(md, &Self::parse_options()) {
             Ok(node) => {
                 let mut words = vec![];
                 // Add the words as nodes.
-                Self::add_node(&node, &mut words, Style::default(), Position::default())?;
+                Self::add_node(
+                    &node,
+                    &mut words,
+                    Style::default(),
+                    Position::default(),
+                    None,
+                    raw_tex,
+                )?;
                 Ok(Self(words))
             }
             Err(error) => Err(Error::Md(error)),
         }
     }
 
+    /// Markdown parse options.
+    /// Single tildes are reserved for joining words, so only double tildes are strikethrough.
+    fn parse_options() -> ParseOptions {
+        ParseOptions {
+            constructs: Constructs::gfm(),
+            gfm_strikethrough_single_tilde: false,
+            ..Default::default()
+        }
+    }
+
     /// A words from a markdown node.
     fn add_node(
         node: &Node,
         words: &mut Vec<Word>,
         style: Style,
         position: Position,
+        language: Option<Language>,
+        raw_tex: bool,
     ) -> Result<(), Error> {
         match node {
             // Add from the root node.
-            Node::Root(node) => node
-                .children
-                .iter()
-                .try_for_each(|child| Self::add_node(child, words, style, position)),
+            Node::Root(node) => {
+                Self::add_children(&node.children, words, style, position, language, raw_tex)
+            }
             Node::InlineCode(node) => {
+                // Inline raw TeX: `tex:\hfill` or `tex[2em]:\kern2em`
+                if raw_tex {
+                    if let Some(captures) = RE_INLINE_TEX.captures(&node.value) {
+                        let width = captures.get(2).map(|w| w.as_str());
+                        Self::add_raw_tex(&captures[3], width, words, style, language)?;
+                        return Ok(());
+                    }
+                }
                 // Treat the inline code as a citation. Create a new node and start to apply TeX commands.
-                let parse_options = ParseOptions {
-                    constructs: Constructs::gfm(),
-                    ..Default::default()
-                };
-                match to_mdast(&node.value, &parse_options) {
-                    Ok(node) => Self::add_node(&node, words, Style::Regular, Position::Margin),
+                match to_mdast(&node.value, &Self::parse_options()) {
+                    Ok(node) => Self::add_node(
+                        &node,
+                        words,
+                        Style::REGULAR,
+                        Position::Margin,
+                        None,
+                        raw_tex,
+                    ),
                     Err(error) => Err(Error::Md(error)),
                 }
             }
-            Node::Emphasis(node) => {
-                // Add an italic style.
-                let style = match style {
-                    Style::Bold => Style::BoldItalic,
-                    _ => Style::Italic,
-                };
-                node.children
-                    .iter()
-                    .try_for_each(|child| Self::add_node(child, words, style, position))
-            }
-            Node::Strong(node) => {
-                // Add a bold style.
-                let style = match style {
-                    Style::Italic => Style::BoldItalic,
-                    _ => Style::Bold,
-                };
-                node.children
-                    .iter()
-                    .try_for_each(|child| Self::add_node(child, words, style, position))
+            // Fenced raw TeX. The info string can optionally include the width: ```tex 2em
+            Node::Code(node) if raw_tex && node.lang.as_deref() == Some("tex") => {
+                Self::add_raw_tex(&node.value, node.meta.as_deref(), words, style, language)
             }
+            // Add an italic style.
+            Node::Emphasis(node) => Self::add_children(
+                &node.children,
+                words,
+                style | Style::ITALIC,
+                position,
+                language,
+                raw_tex,
+            ),
+            // Add a bold style.
+            Node::Strong(node) => Self::add_children(
+                &node.children,
+                words,
+                style | Style::BOLD,
+                position,
+                language,
+                raw_tex,
+            ),
+            // Add a strikethrough style.
+            Node::Delete(node) => Self::add_children(
+                &node.children,
+                words,
+                style | Style::STRIKETHROUGH,
+                position,
+                language,
+                raw_tex,
+            ),
             Node::Text(text) => {
-                Self::add_words(&text.value, words, style, position);
+                Self::add_words(&text.value, words, style, position, language);
                 Ok(())
             }
-            Node::Paragraph(node) => node
-                .children
-                .iter()
-                .try_for_each(|child| Self::add_node(child, words, style, position)),
+            Node::Paragraph(node) => {
+                Self::add_children(&node.children, words, style, position, language, raw_tex)
+            }
             _ => Ok(()),
         }
     }
 
+    /// Add words from sibling nodes.
+    /// Inline HTML tags such as `<u>` and `</u>` are siblings of the text that they style, so they're handled here.
+    fn add_children(
+        children: &[Node],
+        words: &mut Vec<Word>,
+        mut style: Style,
+        position: Position,
+        mut language: Option<Language>,
+        raw_tex: bool,
+    ) -> Result<(), Error> {
+        // The languages that were set before each open `<span>`.
+        let mut languages = vec![];
+        for child in children.iter() {
+            match child {
+                Node::Html(html) => {
+                    if let Some((tag_style, open)) = Self::get_html_style(&html.value) {
+                        style = if open {
+                            style.with(tag_style)
+                        } else {
+                            style.without(tag_style)
+                        };
+                    }
+                    // Set the language: <span lang="he">
+                    else if let Some(captures) = RE_SPAN_LANG.captures(html.value.trim()) {
+                        languages.push(language);
+                        language = Some(Language::from_tag(&captures[1])?);
+                    }
+                    // Some other span.
+                    else if html.value.starts_with("<span") {
+                        languages.push(language);
+                    }
+                    // Revert the language.
+                    else if html.value.trim() == "</span>" {
+                        if let Some(previous) = languages.pop() {
+                            language = previous;
+                        }
+                    }
+                }
+                child => Self::add_node(child, words, style, position, language, raw_tex)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Parse an inline HTML tag. Returns the tag's style and true if this is an opening tag.
+    fn get_html_style(html: &str) -> Option<(Style, bool)> {
+        let tag = html.strip_prefix('<')?.strip_suffix('>')?.trim();
+        let (tag, open) = match tag.strip_prefix('/') {
+            Some(tag) => (tag, false),
+            None => (tag, true),
+        };
+        let style = match tag.to_lowercase().as_str() {
+            "u" => Style::UNDERLINE,
+            "s" | "del" => Style::STRIKETHROUGH,
+            "sc" => Style::SMALL_CAPS,
+            "sup" => Style::SUPERSCRIPT,
+            "sub" => Style::SUBSCRIPT,
+            _ => return None,
+        };
+        Some((style, open))
+    }
+
+    /// Add raw TeX as a single word. `width` is an optional length string such as `"2em"`.
+    fn add_raw_tex(
+        tex: &str,
+        width: Option<&str>,
+        words: &mut Vec<Word>,
+        style: Style,
+        language: Option<Language>,
+    ) -> Result<(), Error> {
+        let width = match width.map(str::trim).filter(|w| !w.is_empty()) {
+            Some(width) => Some(width.parse()?),
+            None => None,
+        };
+        words.push(Word {
+            word: tex.trim().to_string(),
+            style,
+            position: Position::Body,
+            language,
+            raw_tex: Some(RawTex { width }),
+        });
+        Ok(())
+    }
+
     /// Split a string into words and add them to `words`.
-    fn add_words(value: &str, words: &mut Vec<Word>, style: Style, position: Position) {
-        value.split(' ').filter(|s| !s.is_empty()).for_each(|w| {
-            words.push(Word {
-                word: w.to_string(),
-                style,
-                position,
+    /// Words are separated by any Unicode whitespace except for non-breaking spaces.
+    fn add_words(
+        value: &str,
+        words: &mut Vec<Word>,
+        style: Style,
+        position: Position,
+        language: Option<Language>,
+    ) {
+        Self::join_words(value)
+            .split(|c: char| c.is_whitespace() && c != NBSP)
+            .filter(|s| !s.is_empty())
+            .for_each(|w| {
+                words.push(Word {
+                    word: w.to_string(),
+                    style,
+                    position,
+                    language,
+                    raw_tex: None,
+                })
+            });
+    }
+
+    /// Replace every non-breaking space, and every `~` between two non-whitespace characters, with `NBSP`.
+    /// For example, `Rabbi~Akiva` will be a single word.
+    fn join_words(value: &str) -> String {
+        let chars = value.chars().collect::<Vec<char>>();
+        chars
+            .iter()
+            .enumerate()
+            .map(|(i, c)| {
+                if NON_BREAKING_SPACES.contains(c)
+                    || (*c == '~'
+                        && i > 0
+                        && i + 1 < chars.len()
+                        && !chars[i - 1].is_whitespace()
+                        && !chars[i + 1].is_whitespace())
+                {
+                    NBSP
+                } else {
+                    *c
+                }
             })
-        });
+            .collect()
     }
 }
 
@@ -106,32 +279,34 @@ impl Span {
 mod tests {
     use super::Span;
 
-    use super::{Position, Style};
+    use super::{Language, Position, Style, NBSP};
+
+    use crate::page::Length;
 
     #[test]
     fn test_words() {
         let md = "Regular *italic* **bold and *italic*** and **this**";
-        let span = Span::from_md(md).unwrap();
+        let span = Span::from_md(md, false).unwrap();
         assert_eq!(&span.0[0].word, "Regular");
-        assert_eq!(span.0[0].style, Style::Regular);
+        assert_eq!(span.0[0].style, Style::REGULAR);
 
         assert_eq!(&span.0[1].word, "italic");
-        assert_eq!(span.0[1].style, Style::Italic);
+        assert_eq!(span.0[1].style, Style::ITALIC);
 
         assert_eq!(&span.0[2].word, "bold");
-        assert_eq!(span.0[2].style, Style::Bold);
+        assert_eq!(span.0[2].style, Style::BOLD);
 
         assert_eq!(&span.0[3].word, "and");
-        assert_eq!(span.0[3].style, Style::Bold);
+        assert_eq!(span.0[3].style, Style::BOLD);
 
         assert_eq!(&span.0[4].word, "italic");
-        assert_eq!(span.0[4].style, Style::BoldItalic);
+        assert_eq!(span.0[4].style, Style::BOLD | Style::ITALIC);
 
         assert_eq!(&span.0[5].word, "and");
-        assert_eq!(span.0[5].style, Style::Regular);
+        assert_eq!(span.0[5].style, Style::REGULAR);
 
         assert_eq!(&span.0[6].word, "this");
-        assert_eq!(span.0[6].style, Style::Bold);
+        assert_eq!(span.0[6].style, Style::BOLD);
 
         for word in span.0.iter() {
             assert_eq!(word.position, Position::Body);
@@ -141,14 +316,98 @@ mod tests {
     #[test]
     fn test_footnote() {
         let md = "A `footnote` *here*";
-        let span = Span::from_md(md).unwrap();
+        let span = Span::from_md(md, false).unwrap();
         assert_eq!(&span.0[0].word, "A");
-        assert_eq!(span.0[0].style, Style::Regular);
+        assert_eq!(span.0[0].style, Style::REGULAR);
         assert_eq!(span.0[0].position, Position::Body);
         assert_eq!(&span.0[1].word, "footnote");
         assert_eq!(span.0[1].position, Position::Margin);
-        assert_eq!(span.0[1].style, Style::Regular);
+        assert_eq!(span.0[1].style, Style::REGULAR);
         assert_eq!(span.0[2].position, Position::Body);
-        assert_eq!(span.0[2].style, Style::Italic);
+        assert_eq!(span.0[2].style, Style::ITALIC);
+    }
+
+    #[test]
+    fn test_inline_styles() {
+        let md = "<sc>Rabbi</sc> Akiva ~~said~~ <u>this *and*</u> <sup>2</sup>";
+        let span = Span::from_md(md, false).unwrap();
+        assert_eq!(&span.0[0].word, "Rabbi");
+        assert_eq!(span.0[0].style, Style::SMALL_CAPS);
+        assert_eq!(span.0[1].style, Style::REGULAR);
+        assert_eq!(&span.0[2].word, "said");
+        assert_eq!(span.0[2].style, Style::STRIKETHROUGH);
+        assert_eq!(span.0[3].style, Style::UNDERLINE);
+        assert_eq!(span.0[4].style, Style::UNDERLINE | Style::ITALIC);
+        assert_eq!(&span.0[5].word, "2");
+        assert_eq!(span.0[5].style, Style::SUPERSCRIPT);
+    }
+
+    #[test]
+    fn test_raw_tex() {
+        let md = "A `tex:\\hfill` B `tex[2em]:\\kern2em` C";
+        // Without raw TeX, the code is marginalia.
+        let span = Span::from_md(md, false).unwrap();
+        assert_eq!(span.0[1].position, Position::Margin);
+        assert!(span.0.iter().all(|w| w.raw_tex.is_none()));
+
+        let span = Span::from_md(md, true).unwrap();
+        assert_eq!(span.0.len(), 5);
+        assert_eq!(&span.0[1].word, "\\hfill");
+        assert_eq!(span.0[1].position, Position::Body);
+        assert_eq!(span.0[1].raw_tex.as_ref().unwrap().width, None);
+        assert_eq!(&span.0[3].word, "\\kern2em");
+        assert_eq!(
+            span.0[3].raw_tex.as_ref().unwrap().width,
+            Some(Length::em(2.))
+        );
+
+        let md = "A\n```tex 1.5em\n\\S\n```\nB";
+        let span = Span::from_md(md, true).unwrap();
+        assert_eq!(span.0.len(), 3);
+        assert_eq!(&span.0[1].word, "\\S");
+        assert_eq!(
+            span.0[1].raw_tex.as_ref().unwrap().width,
+            Some(Length::em(1.5))
+        );
+    }
+
+    #[test]
+    fn test_whitespace() {
+        let md = "One\ttwo\nthree\u{2003}four\u{3000}five";
+        let span = Span::from_md(md, false).unwrap();
+        assert_eq!(
+            span.0
+                .iter()
+                .map(|w| w.word.as_str())
+                .collect::<Vec<&str>>(),
+            ["One", "two", "three", "four", "five"]
+        );
+    }
+
+    #[test]
+    fn test_non_breaking() {
+        let md = "Rabbi~Akiva said ~5 things in\u{00A0}\u{202F}total ~~struck~~";
+        let span = Span::from_md(md, false).unwrap();
+        assert_eq!(span.0.len(), 6);
+        assert_eq!(span.0[0].word, format!("Rabbi{}Akiva", NBSP));
+        assert_eq!(&span.0[2].word, "~5");
+        assert_eq!(span.0[4].word, format!("in{}{}total", NBSP, NBSP));
+        assert_eq!(&span.0[5].word, "struck");
+        assert_eq!(span.0[5].style, Style::STRIKETHROUGH);
+    }
+
+    #[test]
+    fn test_language() {
+        let md = "The verse <span lang=\"he\">בראשית *ברא*</span> and <span lang=\"la\">in principio</span> `note`";
+        let span = Span::from_md(md, false).unwrap();
+        assert_eq!(span.0[0].language, None);
+        assert_eq!(span.0[2].language, Some(Language::Hebrew));
+        assert_eq!(span.0[3].language, Some(Language::Hebrew));
+        assert_eq!(span.0[3].style, Style::ITALIC);
+        assert_eq!(span.0[4].language, None);
+        assert_eq!(span.0[5].language, Some(Language::Latin));
+        assert_eq!(span.0[6].language, Some(Language::Latin));
+        assert_eq!(span.0[7].language, None);
+        assert!(Span::from_md("<span lang=\"xx\">x</span>", false).is_err());
     }
 }
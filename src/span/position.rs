@@ -1,7 +1,7 @@
 use super::LatexCommand;
 
 /// The position of a word on a page.
-#[derive(Copy, Clone, Default, Eq, PartialEq, Debug)]
+#[derive(Copy, Clone, Default, Eq, PartialEq, Ord, PartialOrd, Debug)]
 pub enum Position {
     /// Just some regular ole text.
     #[default]
@@ -12,14 +12,19 @@ pub enum Position {
 
 impl Position {
     /// Get a command to start or end a margin note.
-    pub fn get_command(&self, position: &Position) -> LatexCommand {
+    /// `marginalia_font` is the command that sets the marginalia font. If None, marginalia are tiny.
+    pub fn get_command(&self, position: &Position, marginalia_font: Option<&str>) -> LatexCommand {
         match (self, position) {
             // Switch to marginalia.
-            (Position::Body, Position::Margin) => {
-                (Some("\\marginnote{\\noindent\\justifying\\tiny "), None)
-            }
+            (Position::Body, Position::Margin) => (
+                Some(format!(
+                    "\\marginnote{{\\noindent\\justifying{} ",
+                    marginalia_font.unwrap_or("\\tiny")
+                )),
+                None,
+            ),
             // Switch back to the body.
-            (Position::Margin, Position::Body) => (None, Some("}")),
+            (Position::Margin, Position::Body) => (None, Some("}".to_string())),
             _ => unreachable!(),
         }
     }
@@ -0,0 +1,9 @@
+use crate::page::Length;
+
+/// Raw TeX that is emitted verbatim rather than being sanitized.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RawTex {
+    /// The estimated width of the TeX. This is used by Cosmic to measure the line.
+    /// If None, the TeX is assumed to have no width, e.g. `\hfill`.
+    pub width: Option<Length>,
+}
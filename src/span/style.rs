@@ -1,50 +1,177 @@
-use cosmic_text::AttrsOwned;
+use cosmic_text::{AttrsOwned, Metrics};
 
 use crate::font::cosmic_font::CosmicFont;
 
-use super::LatexCommand;
+/// The relative size of superscript and subscript text.
+const SCRIPT_SCALE: f32 = 0.7;
+/// The relative size of small caps text. Small caps are measured as uppercase text at this scale.
+const SMALL_CAPS_SCALE: f32 = 0.8;
 
-#[derive(Eq, PartialEq, Copy, Clone, Debug, Default)]
-pub enum Style {
-    #[default]
-    Regular,
-    Italic,
-    Bold,
-    BoldItalic,
-}
+/// A set of style flags. Styles can be combined, e.g. `Style::BOLD | Style::ITALIC`.
+#[derive(Eq, PartialEq, Ord, PartialOrd, Copy, Clone, Debug, Default)]
+pub struct Style(u8);
 
 impl Style {
-    /// Returns the command required to *start* to *set* the style *from* `self` to `style`
-    /// e.g. if `self == Self::Bold && style == Self::Italic`, then this returns:
-    /// `}\textbf{`
-    pub fn get_command(&self, style: &Self) -> LatexCommand {
-        const TEXTBF: &str = "\\textbf{";
-        const TEXTIT: &str = "\\textit{";
-
-        match (self, style) {
-            (Self::Regular, Self::Italic) | (Self::Bold, Self::BoldItalic) => (Some(TEXTIT), None),
-            (Self::Regular, Self::Bold) | (Self::Italic, Self::BoldItalic) => (Some(TEXTBF), None),
-            (Self::Regular, Self::BoldItalic) => (Some("\\textit{\\textbf{"), None),
-            (Self::Italic | Self::Bold, Self::Regular) => (None, Some("}")),
-            (Self::Italic, Self::Bold) => (Some(TEXTBF), Some("}")),
-            (Self::Bold, Self::Italic) => (Some(TEXTIT), Some("}")),
-            (Self::BoldItalic, Self::Regular) => (None, Some("}}")),
-            (Self::BoldItalic, Self::Italic) => (Some(TEXTIT), Some("}}")),
-            (Self::BoldItalic, Self::Bold) => (Some(TEXTBF), Some("}}")),
-            (Self::Regular, Self::Regular)
-            | (Self::Italic, Self::Italic)
-            | (Self::Bold, Self::Bold)
-            | (Self::BoldItalic, Self::BoldItalic) => (None, None),
+    pub const REGULAR: Self = Self(0);
+    pub const ITALIC: Self = Self(1);
+    pub const BOLD: Self = Self(1 << 1);
+    pub const SMALL_CAPS: Self = Self(1 << 2);
+    pub const UNDERLINE: Self = Self(1 << 3);
+    pub const STRIKETHROUGH: Self = Self(1 << 4);
+    pub const SUPERSCRIPT: Self = Self(1 << 5);
+    pub const SUBSCRIPT: Self = Self(1 << 6);
+
+    /// Every single-flag style, in the order that their TeX commands are opened.
+    const FLAGS: [Self; 7] = [
+        Self::ITALIC,
+        Self::BOLD,
+        Self::SMALL_CAPS,
+        Self::UNDERLINE,
+        Self::STRIKETHROUGH,
+        Self::SUPERSCRIPT,
+        Self::SUBSCRIPT,
+    ];
+
+    /// Returns true if all of the flags in `style` are set in `self`.
+    pub const fn contains(&self, style: Self) -> bool {
+        self.0 & style.0 == style.0
+    }
+
+    /// Returns a copy of `self` with the flags in `style` set.
+    pub const fn with(&self, style: Self) -> Self {
+        Self(self.0 | style.0)
+    }
+
+    /// Returns a copy of `self` with the flags in `style` unset.
+    pub const fn without(&self, style: Self) -> Self {
+        Self(self.0 & !style.0)
+    }
+
+    /// Returns the bold and italic flags, which select the face of the font.
+    pub const fn face(&self) -> Self {
+        Self(self.0 & (Self::BOLD.0 | Self::ITALIC.0))
+    }
+
+    /// Returns the command that opens a single-flag style.
+    const fn get_command(&self) -> &'static str {
+        match self.0 {
+            0b1 => "\\textit{",
+            0b10 => "\\textbf{",
+            0b100 => "\\textsc{",
+            0b1000 => "\\uline{",
+            0b10000 => "\\sout{",
+            0b100000 => "\\textsuperscript{",
+            0b1000000 => "\\textsubscript{",
+            _ => unreachable!(),
         }
     }
 
+    /// Returns the Cosmic attributes of this style.
+    ///
+    /// Underline and strikethrough don't affect the width of the text, so they're ignored.
+    /// Superscript, subscript, and small caps are measured at a smaller font size.
     pub fn attrs(&self, font: &CosmicFont) -> AttrsOwned {
-        match self {
-            Self::Regular => &font.regular,
-            Self::Italic => &font.italic,
-            Self::Bold => &font.bold,
-            Self::BoldItalic => &font.bold_italic,
+        let mut attrs = match (self.contains(Self::BOLD), self.contains(Self::ITALIC)) {
+            (false, false) => &font.regular,
+            (false, true) => &font.italic,
+            (true, false) => &font.bold,
+            (true, true) => &font.bold_italic,
+        }
+        .clone();
+        let scale = if self.contains(Self::SUPERSCRIPT) || self.contains(Self::SUBSCRIPT) {
+            Some(SCRIPT_SCALE)
+        } else if self.contains(Self::SMALL_CAPS) {
+            Some(SMALL_CAPS_SCALE)
+        } else {
+            None
+        };
+        if let Some(scale) = scale {
+            attrs.metrics_opt =
+                Some(Metrics::new(font.metrics.font_size * scale, font.metrics.line_height).into());
         }
-        .clone()
+        attrs
+    }
+
+    /// Returns the text that Cosmic should measure for a word in this style.
+    pub fn cosmic_text(&self, word: &str) -> String {
+        if self.contains(Self::SMALL_CAPS) {
+            word.to_uppercase()
+        } else {
+            word.to_string()
+        }
+    }
+}
+
+impl std::ops::BitOr for Style {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        self.with(rhs)
+    }
+}
+
+/// The styles that are currently open in a TeX string, in the order in which they were opened.
+/// This is used to open and close arbitrarily nested style commands.
+#[derive(Default)]
+pub struct OpenStyles(Vec<Style>);
+
+impl OpenStyles {
+    /// Returns the commands required to set the style to `style` as `(prefix, suffix)`.
+    /// The suffix closes styles that `style` doesn't have and must be added to the previous word.
+    /// The prefix opens styles that `style` has but that aren't open yet.
+    ///
+    /// e.g. if the open styles are italic then bold, and `style` is `Style::BOLD`, this returns:
+    /// `("\\textbf{", "}}")`
+    pub fn set(&mut self, style: Style) -> (String, String) {
+        // Close styles until all of the open styles are in `style`.
+        let mut suffix = String::new();
+        while self.0.iter().any(|s| !style.contains(*s)) {
+            self.0.pop();
+            suffix.push('}');
+        }
+        // Open the missing styles.
+        let mut prefix = String::new();
+        for flag in Style::FLAGS.iter().filter(|f| style.contains(**f)) {
+            if !self.0.contains(flag) {
+                prefix.push_str(flag.get_command());
+                self.0.push(*flag);
+            }
+        }
+        (prefix, suffix)
+    }
+
+    /// Returns the commands required to close all open styles.
+    pub fn close(&mut self) -> String {
+        let suffix = "}".repeat(self.0.len());
+        self.0.clear();
+        suffix
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{OpenStyles, Style};
+
+    #[test]
+    fn test_open_styles() {
+        let mut open = OpenStyles::default();
+        assert_eq!(
+            open.set(Style::BOLD | Style::ITALIC),
+            ("\\textit{\\textbf{".to_string(), String::new())
+        );
+        assert_eq!(
+            open.set(Style::BOLD),
+            ("\\textbf{".to_string(), "}}".to_string())
+        );
+        assert_eq!(
+            open.set(Style::BOLD | Style::UNDERLINE | Style::SUPERSCRIPT),
+            ("\\uline{\\textsuperscript{".to_string(), String::new())
+        );
+        assert_eq!(
+            open.set(Style::BOLD | Style::UNDERLINE),
+            (String::new(), "}".to_string())
+        );
+        assert_eq!(open.close(), "}}");
+        assert_eq!(open.close(), "");
     }
 }
@@ -1,4 +1,4 @@
-use super::{position::Position, style::Style};
+use super::{language::Language, position::Position, raw_tex::RawTex, style::Style};
 
 /// A word and its style.
 #[derive(Clone)]
@@ -9,4 +9,8 @@ pub struct Word {
     pub style: Style,
     /// The position on
     pub position: Position,
+    /// The language of the word. If None, this is the document's default language.
+    pub language: Option<Language>,
+    /// If not None, `word` is raw TeX.
+    pub raw_tex: Option<RawTex>,
 }
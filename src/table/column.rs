@@ -79,14 +79,14 @@ mod tests {
         let column = Column::new(empty, Width::Half);
         assert!(!column.done());
 
-        let span = Span::from_md(MD).unwrap();
+        let span = Span::from_md(MD, false).unwrap();
         let mut span_column = SpanColumn::new(span, CosmicFont::default_left(), "\\font");
         span_column.start = 3;
         let full = MaybeSpanColumn::Span(&mut span_column);
         let column = Column::new(full, Width::Half);
         assert!(!column.done());
 
-        let span = Span::from_md(MD).unwrap();
+        let span = Span::from_md(MD, false).unwrap();
         let len = span.0.len();
         let mut span_column = SpanColumn::new(span, CosmicFont::default_left(), "\\font");
         span_column.start = len;
@@ -8,15 +8,15 @@ use position::{Position, POSITIONS};
 
 use crate::{
     error::Error,
-    page::Page,
+    page::{ColumnRules, Length, Page},
     tex,
-    xetex::{get_num_lines, log_tex},
+    xetex::{get_num_grid_lines, get_num_lines, log_tex},
 };
 
 mod column;
 pub(crate) mod maybe_span_column;
 mod para_column;
-mod position;
+pub(crate) mod position;
 pub(crate) mod span_column;
 mod width;
 
@@ -178,7 +178,13 @@ impl<'t> Table<'t> {
                 tex.push_str(Page::END_DOCUMENT);
 
                 // Get the number of lines per page (which is the same as per column).
-                let num_lines = get_num_lines(&tex)?;
+                // Convert them to lines on the main text's baseline grid.
+                let num_lines = self
+                    .get_num_lines(&tex)?
+                    .into_iter()
+                    .zip(positions.iter())
+                    .map(|(num_lines, position)| self.get_grid_num_lines(*position, num_lines))
+                    .collect::<Vec<usize>>();
 
                 // Get the minimum number of lines.
                 Ok(match num_lines.into_iter().enumerate().min_by(|a, b| a.1.cmp(&b.1)) {
@@ -219,7 +225,7 @@ impl<'t> Table<'t> {
             log_tex(&tex);
         }
 
-        get_num_lines(&tex)
+        self.get_num_lines(&tex)
     }
 
     /// Given a target `num_lines`, generate a TeX string of the table.
@@ -243,7 +249,7 @@ impl<'t> Table<'t> {
         if Self::para_columns_done(&para_columns) {
             Ok(None)
         } else {
-            Ok(self.get_paracol(&para_columns))
+            Ok(self.get_ruled_paracol(para_columns))
         }
     }
 
@@ -261,27 +267,71 @@ impl<'t> Table<'t> {
                 None => ParaColumn::None,
             };
         }
-        self.get_paracol(&para_columns).unwrap()
+        self.get_ruled_paracol(para_columns).unwrap()
     }
 
     /// Returns a table with text on the left and right, and the title in the center.
+    /// `title` is the TeX of the title.
+    ///
+    /// The number of lines that the title fills is measured, and the left and right columns are filled to that number of lines.
     pub fn get_title_table(&mut self, title: &str) -> Result<Option<String>, Error> {
-        const NUM_LINES: usize = 4;
+        let num_lines = self.get_title_num_lines(title)?;
 
-        let left = self.get_para_column(Position::Left, None, NUM_LINES)?;
-        let right = self.get_para_column(Position::Right, None, NUM_LINES)?;
+        let left = self.get_para_column(Position::Left, None, num_lines)?;
+        let right = self.get_para_column(Position::Right, None, num_lines)?;
 
         // The center is the title.
-        // \begin{center}\centerfont{\huge{Talmudifier}}\end{center}
-        let title = format!(
-            "{}\\centerfont{{{}}}{}",
-            tex!("begin", "center"),
-            tex!("huge", &title),
-            tex!("end", "center")
+        let center = ParaColumn::Text(title.to_string());
+
+        Ok(self.get_ruled_paracol([left, center, right]))
+    }
+
+    /// Returns the number of lines on the main text's baseline grid that the `title` fills in the center column.
+    ///
+    /// The title is typeset in a box that isn't shown.
+    /// Then, there is one line of text per main line skip of the height of the box, which XeTeX can count.
+    fn get_title_num_lines(&self, title: &str) -> Result<usize, Error> {
+        let skip = Length::pt(self.page.font_metrics.skip);
+        // \numexpr rounds, so this is the height divided by the skip, rounded up.
+        let center = format!(
+            "\\setbox0\\vbox{{{}}}\\count255=\\numexpr(\\ht0 + \\dp0 + \\dimexpr {}\\relax / 2 - 1) / \\dimexpr {}\\relax\\relax\n\\loop\\ifnum\\count255>0 \\noindent x\\par\\advance\\count255 by -1 \\repeat",
+            title, skip, skip
         );
-        let center = ParaColumn::Text(title);
+        // Preserve the width of the center column.
+        let para_columns = [
+            self.get_empty_para_column(Position::Left),
+            ParaColumn::Text(center),
+            self.get_empty_para_column(Position::Right),
+        ];
+        let mut tex = self.page.preamble.clone().ok_or(Error::NoPreamble)?;
+        tex.push_str(&self.get_paracol(&para_columns).unwrap());
+        tex.push_str(Page::END_DOCUMENT);
 
-        Ok(self.get_paracol(&[left, center, right]))
+        // Log.
+        if self.log {
+            log_tex(&tex);
+        }
+
+        // Always fill at least one line.
+        Ok(self.get_num_lines(&tex)?.into_iter().sum::<usize>().max(1))
+    }
+
+    /// Returns the number of lines per page.
+    /// If the page has a baseline grid, this is the number of grid lines.
+    fn get_num_lines(&self, tex: &str) -> Result<Vec<usize>, Error> {
+        if self.page.baseline_grid {
+            get_num_grid_lines(tex, self.page.font_metrics.skip)
+        } else {
+            get_num_lines(tex)
+        }
+    }
+
+    /// Returns an empty column if there is a column at `position`.
+    fn get_empty_para_column(&self, position: Position) -> ParaColumn {
+        match self.get_column(position) {
+            Column::None => ParaColumn::None,
+            _ => ParaColumn::Empty,
+        }
     }
 
     /// Returns true if none of the columns have any further text.
@@ -295,7 +345,7 @@ impl<'t> Table<'t> {
     ///
     /// - `position` is the position of the column.
     /// - `target_position` is the position of the column with the least number of lines.
-    /// - `num_lines` is the target number of lines.
+    /// - `num_lines` is the target number of lines on the main text's baseline grid.
     fn get_para_column(
         &mut self,
         position: Position,
@@ -312,6 +362,8 @@ impl<'t> Table<'t> {
                     None => ParaColumn::None,
                 }
             } else {
+                // Convert the number of lines to the column's line skip.
+                let num_lines = self.get_column_num_lines(position, num_lines);
                 // Guess the starting index.
                 match self.get_cosmic_index(position, num_lines) {
                     Some(cosmic_index) => {
@@ -419,6 +471,39 @@ impl<'t> Table<'t> {
         }
     }
 
+    /// Convert TeX strings per column into a TeX table in the final document.
+    ///
+    /// If the column rules are drawn with TikZ, the top and bottom of each column are marked, and the rules are drawn at the end of the last column.
+    /// Empty columns are marked too, because paracol draws rules next to them.
+    fn get_ruled_paracol(&self, mut columns: [ParaColumn; 3]) -> Option<String> {
+        if !self
+            .page
+            .column_rules
+            .as_ref()
+            .is_some_and(|rules| rules.is_tikz())
+            || !columns.iter().any(|c| matches!(c, ParaColumn::Text(_)))
+        {
+            return self.get_paracol(&columns);
+        }
+        let indices = (0..columns.len())
+            .filter(|i| !matches!(columns[*i], ParaColumn::None))
+            .collect::<Vec<usize>>();
+        for i in indices.iter() {
+            let text = match &columns[*i] {
+                ParaColumn::Text(text) => text.as_str(),
+                _ => "",
+            };
+            columns[*i] = ParaColumn::Text(ColumnRules::mark_column(text, *i));
+        }
+        if let Some(ParaColumn::Text(text)) = indices.last().map(|i| &mut columns[*i]) {
+            for pair in indices.windows(2) {
+                text.push_str(&ColumnRules::draw_rule(pair[0], pair[1]));
+            }
+        }
+        self.get_paracol(&columns)
+            .map(|table| format!("{}\n{}", ColumnRules::BEGIN_TABLE, table))
+    }
+
     /// Returns three [`ParaColumn`]s that can be used to calculate a column's number of lines.
     /// This function preserves the width of the target column while removing text from the other columns.
     fn get_paracolumns_for_num_lines(
@@ -635,7 +720,8 @@ impl<'t> Table<'t> {
     /// Use Cosmic Text to guess the initial end index that will be used to fill a TeX column.
     fn get_cosmic_index(&mut self, position: Position, num_lines: usize) -> Option<usize> {
         let page_width = self.page.table_width;
-        let separation = (self.num_columns - 1) as f32 * self.page.column_separation.get_pts();
+        let separation = (self.num_columns - 1) as f32
+            * self.page.column_separation.get_pts(&self.page.font_metrics);
 
         match self.get_mut_column(position) {
             Column::Column { column, width } => {
@@ -645,6 +731,10 @@ impl<'t> Table<'t> {
                         if column.start >= len {
                             None
                         } else {
+                            // The font system is locked until the column has been measured.
+                            let font_system = column.cosmic_font.font_system.clone();
+                            let mut database = font_system.lock();
+                            let font_system = &mut database.font_system;
                             // Iterate through the slice.
                             for end in column.start..len {
                                 // Ignore marginalia.
@@ -654,30 +744,23 @@ impl<'t> Table<'t> {
                                 // Get the width of the column in pts.
                                 let column_width = page_width * width.column_ratio() - separation;
                                 // Prepare the Cosmic buffer.
-                                let mut buffer = Buffer::new(
-                                    &mut column.cosmic_font.font_system,
-                                    column.cosmic_font.metrics,
-                                );
+                                let mut buffer =
+                                    Buffer::new(font_system, column.cosmic_font.metrics);
 
                                 // Set the width.
-                                buffer.set_size(
-                                    &mut column.cosmic_font.font_system,
-                                    Some(column_width),
-                                    None,
-                                );
+                                buffer.set_size(font_system, Some(column_width), None);
 
                                 // Get the Cosmic spans.
                                 let spans = column.to_cosmic(end);
                                 // Set the text.
                                 buffer.set_rich_text(
-                                    &mut column.cosmic_font.font_system,
+                                    font_system,
                                     spans.iter().map(|(s, a)| (s.as_str(), a.as_attrs())),
                                     column.cosmic_font.regular.as_attrs(),
                                     Shaping::Advanced,
                                 );
                                 // Create lines.
-                                buffer
-                                    .shape_until_scroll(&mut column.cosmic_font.font_system, true);
+                                buffer.shape_until_scroll(font_system, true);
                                 // Return the number of lines.
                                 let num = buffer.layout_runs().count();
                                 if num > num_lines {
@@ -694,6 +777,31 @@ impl<'t> Table<'t> {
         }
     }
 
+    /// Convert a number of lines on the main text's baseline grid into a number of lines in the column at `position`.
+    /// The column's lines never exceed the height of the main text's lines, so that the columns line up at table boundaries.
+    fn get_column_num_lines(&self, position: Position, num_lines: usize) -> usize {
+        match self.get_skip_ratio(position) {
+            // Always fill at least one line.
+            Some(ratio) => ((num_lines as f32 / ratio + f32::EPSILON).floor() as usize).max(1),
+            None => num_lines,
+        }
+    }
+
+    /// Convert a number of lines in the column at `position` into a number of lines on the main text's baseline grid.
+    fn get_grid_num_lines(&self, position: Position, num_lines: usize) -> usize {
+        match self.get_skip_ratio(position) {
+            Some(ratio) => (num_lines as f32 * ratio - f32::EPSILON).ceil() as usize,
+            None => num_lines,
+        }
+    }
+
+    /// Returns the ratio of the column's line skip to the main text's line skip.
+    fn get_skip_ratio(&self, position: Position) -> Option<f32> {
+        self.get_column(position)
+            .get_span_column()
+            .map(|column| column.cosmic_font.metrics.line_height / self.page.font_metrics.skip)
+    }
+
     fn para_columns_done(para_columns: &[ParaColumn; 3]) -> bool {
         para_columns.iter().all(|p| matches!(p, ParaColumn::None))
     }
@@ -701,6 +809,8 @@ impl<'t> Table<'t> {
 
 #[cfg(test)]
 mod tests {
+    use cosmic_text::Metrics;
+
     use crate::{
         font::{cosmic_font::CosmicFont, tex_fonts::TexFonts},
         page::Page,
@@ -714,7 +824,7 @@ mod tests {
     #[test]
     fn test_cosmic_index() {
         let lorem = include_str!("../../test_text/lorem.txt");
-        let span = Span::from_md(lorem).unwrap();
+        let span = Span::from_md(lorem, false).unwrap();
         assert_eq!(span.0.len(), 402);
         let cosmic_font = CosmicFont::default_left();
         let tex_fonts = TexFonts::new().unwrap();
@@ -742,12 +852,34 @@ mod tests {
         assert_eq!(cosmic_index, 46);
     }
 
+    #[test]
+    fn test_column_num_lines() {
+        let span = Span::from_md("A commentary in a smaller font.", false).unwrap();
+        let mut cosmic_font = CosmicFont::default_left();
+        cosmic_font.metrics = Metrics::new(9., 11.);
+        let mut column = SpanColumn::new(span, cosmic_font, "\\leftfont");
+        let page = Page::default();
+        let table = Table::new(
+            Some(MaybeSpanColumn::Span(&mut column)),
+            None,
+            Some(MaybeSpanColumn::Empty),
+            &page,
+            false,
+        );
+        // 10 lines of 13pt main text are 130pt tall, which fits 11 lines of 11pt commentary.
+        assert_eq!(table.get_column_num_lines(Position::Left, 10), 11);
+        // 11 lines of commentary are 121pt tall, which is 10 lines of main text.
+        assert_eq!(table.get_grid_num_lines(Position::Left, 11), 10);
+        assert_eq!(table.get_column_num_lines(Position::Left, 1), 1);
+        assert_eq!(table.get_column_num_lines(Position::Right, 10), 10);
+    }
+
     #[test]
     fn test_min_num_lines() {
         let (left, center, right) = get_test_md();
-        let left = Span::from_md(&left).unwrap();
-        let center = Span::from_md(&center).unwrap();
-        let right = Span::from_md(&right).unwrap();
+        let left = Span::from_md(&left, false).unwrap();
+        let center = Span::from_md(&center, false).unwrap();
+        let right = Span::from_md(&right, false).unwrap();
 
         let tex_fonts = TexFonts::new().unwrap();
 
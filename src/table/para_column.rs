@@ -58,7 +58,7 @@ mod tests {
 
     #[test]
     fn test_para_column() {
-        let span = Span::from_md("There are so many words in this sentence!").unwrap();
+        let span = Span::from_md("There are so many words in this sentence!", false).unwrap();
         let mut span_column = SpanColumn::new(span, CosmicFont::default_left(), "\\font");
         span_column.start = 6;
         let full = MaybeSpanColumn::Span(&mut span_column);
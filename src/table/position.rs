@@ -1,5 +1,5 @@
 /// The position of a column on the page.
-#[derive(Copy, Clone, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum Position {
     Left,
     Center,
@@ -1,10 +1,17 @@
-use cosmic_text::AttrsOwned;
+use std::collections::BTreeMap;
+
+use cosmic_text::{AttrsOwned, Metrics};
 use lazy_static::lazy_static;
 use regex::Regex;
 
 use crate::{
-    font::cosmic_font::CosmicFont,
-    span::{position::Position, style::Style, Span},
+    font::{cosmic_font::CosmicFont, font_metrics::FontMetrics, glyph_coverage::FaceChar},
+    span::{
+        language::Language,
+        position::Position,
+        style::{OpenStyles, Style},
+        Span, NBSP,
+    },
 };
 
 lazy_static! {
@@ -12,8 +19,18 @@ lazy_static! {
     static ref RE_PUNCTUATION: Regex = Regex::new(r#"^(!|;|:|,|\.)"#).unwrap();
     static ref RE_QUOTES: Regex = Regex::new(r#"([“|"](.*?)[”|"])"#).unwrap();
     static ref RE_SPECIAL_CHARS: Regex = Regex::new(r#"(#|\$|%|&|_)"#).unwrap();
+    static ref RE_RAW_TEX: Regex = Regex::new("\u{E000}(\\d+)\u{E001}").unwrap();
 }
 
+/// The start and end of a raw TeX placeholder in `SpanColumn::to_tex`.
+/// These are private-use characters, and they are removed from the words so that a placeholder can't appear in the source text.
+const RAW_TEX_START: char = '\u{E000}';
+const RAW_TEX_END: char = '\u{E001}';
+
+/// An em dash is one em wide in practically every font.
+/// Raw TeX is measured in Cosmic as an em dash scaled to the TeX's declared width.
+const RAW_TEX_PLACEHOLDER: &str = "—";
+
 /// A column of text that can be typeset.
 /// Columns try to fill a target number of lines with words.
 /// Cosmic is used to get an initial guess at the number of words.
@@ -29,6 +46,16 @@ pub struct SpanColumn {
     pub cosmic_font: CosmicFont,
     /// The command to set the TeX font.
     pub tex_font: String,
+    /// Characters that the TeX font doesn't have, keyed by position and face, and the commands of the fallback fonts that do.
+    pub fallbacks: BTreeMap<FaceChar, String>,
+    /// The Cosmic fonts of the fallback fonts, keyed by their TeX commands.
+    pub fallback_fonts: BTreeMap<String, CosmicFont>,
+    /// The command to set the marginalia font. If None, marginalia are set in a tiny version of the column's font.
+    pub marginalia_font: Option<String>,
+    /// The languages that have their own fonts. Text in any other language is set in the column's font.
+    pub font_languages: Vec<Language>,
+    /// The indices of words whose widths are changed by the font's OpenType features, and how much to scale them by when measuring them in Cosmic.
+    pub feature_scales: BTreeMap<usize, f32>,
 }
 
 impl SpanColumn {
@@ -38,9 +65,29 @@ impl SpanColumn {
             start: 0,
             cosmic_font,
             tex_font: tex_font.to_string(),
+            fallbacks: BTreeMap::default(),
+            fallback_fonts: BTreeMap::default(),
+            marginalia_font: None,
+            font_languages: vec![],
+            feature_scales: BTreeMap::default(),
         }
     }
 
+    /// Measure each word with the font's OpenType features and variable font axes and set `feature_scales`.
+    pub fn set_feature_scales(&mut self) {
+        if self.cosmic_font.features.is_empty() && self.cosmic_font.variations.is_empty() {
+            return;
+        }
+        self.feature_scales = (0..self.span.0.len())
+            .filter(|index| self.span.0[*index].raw_tex.is_none())
+            .filter_map(|index| {
+                self.cosmic_font
+                    .get_feature_scale(self.span.0[index].style, &self.get_cosmic_text(index))
+                    .map(|scale| (index, scale))
+            })
+            .collect();
+    }
+
     pub fn is_word_in_body(&self, index: usize) -> bool {
         self.span.0[index].position == Position::Body
     }
@@ -59,17 +106,41 @@ impl SpanColumn {
         // The current Cosmic formatting attributes.
         let mut attrs = self.cosmic_font.regular.clone();
 
+        // The fallback font command of the last span, and that span's index, if the previous word ended in a fallback font.
+        let mut fallback_end = None;
+
         // Iterate through the words. Ignore citations.
-        for word in self.span.0[self.start..end]
+        for (index, word) in self.span.0[self.start..end]
             .iter()
-            .filter(|w| w.position == Position::Body)
+            .enumerate()
+            .map(|(i, w)| (self.start + i, w))
+            .filter(|(_, w)| w.position == Position::Body)
         {
-            // Add the word to the current span.
-            if style == word.style {
-                span.push(word.word.clone());
+            // Raw TeX is measured as a placeholder of the same width.
+            // Raw TeX without a width is ignored.
+            if let Some(raw_tex) = &word.raw_tex {
+                if let Some(width) = &raw_tex.width {
+                    // Finish the span, including the space before the placeholder.
+                    if !span.is_empty() {
+                        span.push(String::new());
+                        cosmic_spans.push((span.join(" "), attrs.clone()));
+                        span.clear();
+                    }
+                    let width = width.get_pts(&FontMetrics {
+                        size: self.cosmic_font.metrics.font_size,
+                        skip: self.cosmic_font.metrics.line_height,
+                    });
+                    let mut placeholder_attrs = attrs.clone();
+                    placeholder_attrs.metrics_opt =
+                        Some(Metrics::new(width, self.cosmic_font.metrics.line_height).into());
+                    cosmic_spans.push((RAW_TEX_PLACEHOLDER.to_string(), placeholder_attrs));
+                    // The next word is preceded by a space.
+                    span.push(String::new());
+                }
+                continue;
             }
             // The style changed. Finish the span and set a new style.
-            else {
+            if style != word.style {
                 cosmic_spans.push((span.join(" "), attrs));
                 // Reset the span.
                 span.clear();
@@ -78,10 +149,71 @@ impl SpanColumn {
                 // Remember the style.
                 style = word.style;
             }
+            // Add the word to the current span.
+            let text = self.get_cosmic_text(index);
+            match self.get_fallback_runs(&text, Position::Body, word.style) {
+                Some(runs) => {
+                    // The previous word ended in the same fallback font as this word's first run.
+                    // Continue that span so that the space between them is measured in the fallback font, like in TeX.
+                    let continues = span.len() == 1
+                        && span[0].is_empty()
+                        && fallback_end.is_some_and(|(command, i)| {
+                            i + 1 == cosmic_spans.len() && runs[0].1 == Some(command)
+                        });
+                    // Finish the span, including the space before this word.
+                    if !continues && !span.is_empty() {
+                        span.push(String::new());
+                        cosmic_spans.push((span.join(" "), attrs.clone()));
+                        span.clear();
+                    }
+                    // Measure each run in its own font.
+                    fallback_end = None;
+                    for (i, (run, command)) in runs.into_iter().enumerate() {
+                        if i == 0 && continues {
+                            let last = cosmic_spans.last_mut().unwrap();
+                            last.0.push(' ');
+                            last.0.push_str(&run);
+                        } else {
+                            let run_attrs = match command
+                                .and_then(|command| self.fallback_fonts.get(command))
+                            {
+                                Some(font) => word.style.attrs(font),
+                                None => attrs.clone(),
+                            };
+                            cosmic_spans.push((run, run_attrs));
+                        }
+                        fallback_end = command.map(|command| (command, cosmic_spans.len() - 1));
+                    }
+                    // The next word is preceded by a space.
+                    span.clear();
+                    span.push(String::new());
+                }
+                None => match self.feature_scales.get(&index) {
+                    // Measure the word at a scaled font size so that it's as wide as it is with the font's features.
+                    Some(scale) => {
+                        if !span.is_empty() {
+                            span.push(String::new());
+                            cosmic_spans.push((span.join(" "), attrs.clone()));
+                            span.clear();
+                        }
+                        let mut scaled_attrs = attrs.clone();
+                        scaled_attrs.metrics_opt = Some(
+                            Metrics::new(
+                                self.cosmic_font.metrics.font_size * scale,
+                                self.cosmic_font.metrics.line_height,
+                            )
+                            .into(),
+                        );
+                        cosmic_spans.push((text, scaled_attrs));
+                        span.push(String::new());
+                    }
+                    None => span.push(text),
+                },
+            }
         }
 
-        // Push the last span.
-        if !span.is_empty() {
+        // Push the last span, unless it's just the space after the previous word.
+        if span.iter().any(|word| !word.is_empty()) {
             cosmic_spans.push((span.join(" "), attrs));
         }
         cosmic_spans
@@ -98,40 +230,61 @@ impl SpanColumn {
         // Build a column.
         let mut text = self.tex_font.to_string();
         let mut style = Style::default();
+        let mut open_styles = OpenStyles::default();
         let mut position = Position::default();
+        let mut language = None;
+        // Raw TeX strings. These are replaced with placeholders until the text is sanitized.
+        let mut raw_tex = vec![];
+        // The fallback font command of the previous word's last group, and where that group ends in `text`.
+        let mut fallback_end: Option<(&String, usize)> = None;
         for word in self.span.0[self.start..end].iter() {
+            // Ignore marginalia.
+            if !marginalia && word.position == Position::Margin {
+                continue;
+            }
             let mut prefixes = vec![];
             let mut suffixes = vec![];
-            // We changed the style.
-            if style != word.style {
-                let (prefix, suffix) = style.get_command(&word.style);
-                if let Some(prefix) = prefix {
+            // Styles are nested inside of languages, which are nested inside of margin notes.
+            // If an outer group changes, the inner groups are closed and re-opened.
+            let position_changed = position != word.position;
+            let language_changed = position_changed || language != word.language;
+            // Close the styles.
+            if language_changed {
+                suffixes.push(open_styles.close());
+            }
+            // Close the language.
+            if language_changed && language.is_some() {
+                suffixes.push("}".to_string());
+            }
+            // Change the position.
+            if position_changed {
+                let command = position.get_command(&word.position, self.marginalia_font.as_deref());
+                if let Some(prefix) = command.0 {
                     prefixes.push(prefix);
                 }
                 // Add a suffix to the previous word.
-                if let Some(suffix) = suffix {
+                if let Some(suffix) = command.1 {
                     suffixes.push(suffix);
                 }
-                style = word.style;
-            }
-            // Change the position.
-            if marginalia {
-                if position != word.position {
-                    let command = position.get_command(&word.position);
-                    if let Some(prefix) = command.0 {
-                        prefixes.push(prefix);
-                    }
-                    // Add a suffix to the previous word.
-                    if let Some(suffix) = command.1 {
-                        suffixes.push(suffix);
-                    }
 
-                    position = word.position;
+                position = word.position;
+            }
+            // Open the language.
+            if language_changed {
+                if let Some(language) = &word.language {
+                    prefixes.push(
+                        language.get_command(self.get_language_font(*language, word.position)),
+                    );
                 }
+                language = word.language;
             }
-            // Ignore marginalia.
-            else if word.position == Position::Margin {
-                continue;
+            // We changed the style.
+            if language_changed || style != word.style {
+                let (prefix, suffix) = open_styles.set(word.style);
+                prefixes.push(prefix);
+                // Add a suffix to the previous word.
+                suffixes.insert(0, suffix);
+                style = word.style;
             }
 
             // Add the suffixes.
@@ -149,19 +302,45 @@ impl SpanColumn {
             // Add the prefixes.
             prefixes.iter().for_each(|p| text.push_str(p));
             // Add the word.
-            text.push_str(&word.word);
+            if word.raw_tex.is_some() {
+                text.push_str(&format!(
+                    "{}{}{}",
+                    RAW_TEX_START,
+                    raw_tex.len(),
+                    RAW_TEX_END
+                ));
+                raw_tex.push(word.word.as_str());
+                fallback_end = None;
+            } else {
+                fallback_end = self.push_fallback_text(
+                    &mut text,
+                    &word.word.replace([RAW_TEX_START, RAW_TEX_END], ""),
+                    word.position,
+                    word.style,
+                    fallback_end,
+                );
+            }
         }
 
-        // Close off the styles and citations.
-        match style {
-            Style::Regular => (),
-            Style::Bold | Style::Italic => text.push('}'),
-            Style::BoldItalic => text.push_str("}}"),
+        // Close off the styles, languages, and citations.
+        text.push_str(&open_styles.close());
+        if language.is_some() {
+            text.push('}');
         }
         if let Position::Margin = position {
             text.push('}');
         }
         Self::santitize_tex(&mut text);
+        // Non-breaking spaces are ties.
+        text = text.replace(NBSP, "~");
+        // Restore the raw TeX.
+        if !raw_tex.is_empty() {
+            text = RE_RAW_TEX
+                .replace_all(&text, |c: &regex::Captures| {
+                    raw_tex[c[1].parse::<usize>().unwrap()]
+                })
+                .to_string();
+        }
         text
     }
 
@@ -169,8 +348,112 @@ impl SpanColumn {
         self.start >= self.span.0.len()
     }
 
+    /// Returns the text of the word at `index` as it is measured in Cosmic.
+    ///
+    /// Cosmic doesn't hyphenate words. TeX hyphenates them with the language's patterns when it verifies the line count.
+    fn get_cosmic_text(&self, index: usize) -> String {
+        let word = &self.span.0[index];
+        word.style.cosmic_text(&word.word)
+    }
+
+    /// Returns the command of the font that text in `language` at `position` is set in if the language doesn't have its own font.
+    /// Aramaic is set in the Hebrew font if there is one.
+    fn get_language_font(&self, language: Language, position: Position) -> Option<&str> {
+        if self.font_languages.contains(&language)
+            || (language == Language::Aramaic && self.font_languages.contains(&Language::Hebrew))
+        {
+            None
+        } else {
+            match position {
+                Position::Margin => Some(
+                    self.marginalia_font
+                        .as_deref()
+                        .unwrap_or(self.tex_font.as_str()),
+                ),
+                Position::Body => Some(self.tex_font.as_str()),
+            }
+        }
+    }
+
+    /// Split `text` into maximal runs of characters that are set in the same font.
+    /// Each run has the command of its fallback font, or None if it's set in the column's font.
+    /// `position` and `style` are the position and style of the word.
+    ///
+    /// Returns None if every character is set in the column's font.
+    fn get_fallback_runs(
+        &self,
+        text: &str,
+        position: Position,
+        style: Style,
+    ) -> Option<Vec<(String, Option<&String>)>> {
+        let get_command = |c: char| self.fallbacks.get(&(position, style.face(), c));
+        if !text.chars().any(|c| get_command(c).is_some()) {
+            return None;
+        }
+        let mut runs: Vec<(String, Option<&String>)> = vec![];
+        for c in text.chars() {
+            let command = get_command(c);
+            match runs.last_mut() {
+                Some(run) if run.1 == command => run.0.push(c),
+                _ => runs.push((c.to_string(), command)),
+            }
+        }
+        Some(runs)
+    }
+
+    /// Push `word` to `text`, setting the characters that the font doesn't have in their fallback fonts.
+    /// `position` and `style` are the position and style of the word.
+    ///
+    /// `fallback_end` is the fallback group that the previous word ended with and where it ends in `text`.
+    /// If only a space separates it from this word's first group in the same font, the two are set as one group.
+    ///
+    /// Returns the fallback group that this word ends with, if any.
+    fn push_fallback_text<'s>(
+        &'s self,
+        text: &mut String,
+        word: &str,
+        position: Position,
+        style: Style,
+        fallback_end: Option<(&'s String, usize)>,
+    ) -> Option<(&'s String, usize)> {
+        let runs = match self.get_fallback_runs(word, position, style) {
+            Some(runs) => runs,
+            None => {
+                text.push_str(word);
+                return None;
+            }
+        };
+        let continues = fallback_end.is_some_and(|(command, end)| {
+            text.len() == end + 1 && text.ends_with(' ') && runs[0].1 == Some(command)
+        });
+        let mut end = None;
+        for (i, (run, command)) in runs.into_iter().enumerate() {
+            match command {
+                Some(command) => {
+                    if i == 0 && continues {
+                        // Reopen the previous word's group.
+                        text.truncate(text.len() - 2);
+                        text.push(' ');
+                    } else {
+                        text.push('{');
+                        text.push_str(command);
+                        text.push(' ');
+                    }
+                    text.push_str(&run);
+                    text.push('}');
+                    end = Some((command, text.len()));
+                }
+                None => {
+                    text.push_str(&run);
+                    end = None;
+                }
+            }
+        }
+        end
+    }
+
     /// Sanitize a TeX string.
-    fn santitize_tex(tex: &mut String) {
+    pub(crate) fn santitize_tex(tex: &mut String) {
         *tex = RE_SPECIAL_CHARS
             .replace_all(&RE_QUOTES.replace_all(tex, "``$2''"), "\\$1")
             .replace("~", "$\\sim$")
@@ -181,7 +464,13 @@ impl SpanColumn {
 
 #[cfg(test)]
 mod tests {
-    use crate::{font::cosmic_font::CosmicFont, span::Span, table::span_column::SpanColumn};
+    use cosmic_text::Metrics;
+
+    use crate::{
+        font::cosmic_font::CosmicFont,
+        span::{language::Language, position::Position, style::Style, Span},
+        table::span_column::SpanColumn,
+    };
 
     #[test]
     fn test_textit() {
@@ -202,6 +491,46 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_nested_styles() {
+        let md = "<u>underlined *and italic*</u> ~~struck~~ <sc>Rashi</sc>";
+        let column = get_column(md);
+        let tex = column.to_tex(None, true);
+        assert_eq!(
+            tex,
+            "\\font \\uline{underlined \\textit{and italic}} \\sout{struck} \\textsc{Rashi}"
+        )
+    }
+
+    #[test]
+    fn test_non_breaking() {
+        let md = "Rabbi~Akiva said ~5 things";
+        let column = get_column(md);
+        let tex = column.to_tex(None, true);
+        assert_eq!(tex, "\\font Rabbi~Akiva said $\\sim$5 things");
+        let cosmic = column.to_cosmic(column.span.0.len());
+        assert_eq!(cosmic[0].0, "Rabbi\u{00A0}Akiva said ~5 things");
+    }
+
+    #[test]
+    fn test_language() {
+        let md = "*The verse* <span lang=\"he\">*בראשית* ברא</span> `in <span lang=\"la\">principio</span>` *end*";
+        let column = get_column(md);
+        let tex = column.to_tex(None, true);
+        assert_eq!(
+            tex,
+            "\\font \\textit{The verse} \\texthebrew{\\font \\textit{בראשית} ברא} \\marginnote{\\noindent\\justifying\\tiny in \\textlatin{\\font principio}} \\textit{end}"
+        );
+        // Languages with their own fonts are set in them.
+        let mut column = get_column(md);
+        column.font_languages = vec![Language::Hebrew];
+        let tex = column.to_tex(None, false);
+        assert_eq!(
+            tex,
+            "\\font \\textit{The verse} \\texthebrew{\\textit{בראשית} ברא} \\textit{end}"
+        );
+    }
+
     #[test]
     fn test_marginnote() {
         let md = "A `footnote *here* and` *there*";
@@ -213,11 +542,122 @@ mod tests {
     );
         let tex = column.to_tex(None, false);
         assert_eq!(tex, "\\font A \\textit{there}");
+        // Marginalia with their own font.
+        let mut column = get_column(md);
+        column.marginalia_font = Some("\\marginaliafont\\itshape".to_string());
+        assert!(column
+            .to_tex(None, true)
+            .contains("\\marginnote{\\noindent\\justifying\\marginaliafont\\itshape footnote"));
+    }
+
+    #[test]
+    fn test_fallbacks() {
+        let mut column = get_column("Shalom שלום, *α!*");
+        column.fallbacks.insert(
+            (Position::Body, Style::ITALIC, 'α'),
+            "\\fallbackfonta".to_string(),
+        );
+        for c in "שלום".chars() {
+            column.fallbacks.insert(
+                (Position::Body, Style::REGULAR, c),
+                "\\fallbackfontb".to_string(),
+            );
+        }
+        let tex = column.to_tex(None, true);
+        assert_eq!(
+            tex,
+            "\\font Shalom {\\fallbackfontb שלום}, \\textit{{\\fallbackfonta α}!}"
+        );
+        // Cosmic measures the fallback characters in their fallback fonts.
+        column
+            .fallback_fonts
+            .insert("\\fallbackfonta".to_string(), CosmicFont::default_center());
+        let cosmic = column.to_cosmic(column.span.0.len());
+        assert!(cosmic.iter().any(|(s, _)| s == "Shalom "));
+        assert!(cosmic.iter().any(|(s, _)| s == "שלום"));
+        let (_, attrs) = cosmic.iter().find(|(s, _)| s == "α").unwrap();
+        assert_eq!(
+            attrs.family_owned,
+            CosmicFont::default_center().italic.family_owned
+        );
+        // A run of fallback characters that spans several words is set as one group.
+        let mut column = get_column("Shalom שלום עליכם, friend");
+        for c in "שלוםעליכם".chars() {
+            column.fallbacks.insert(
+                (Position::Body, Style::REGULAR, c),
+                "\\fallbackfontb".to_string(),
+            );
+        }
+        assert_eq!(
+            column.to_tex(None, true),
+            "\\font Shalom {\\fallbackfontb שלום עליכם}, friend"
+        );
+        let cosmic = column.to_cosmic(column.span.0.len());
+        assert!(cosmic.iter().any(|(s, _)| s == "שלום עליכם"));
+    }
+
+    #[test]
+    fn test_raw_tex() {
+        let column = get_raw_tex_column("100% `tex:\\hfill` `tex[1em]:\\kern1em` $5");
+        let tex = column.to_tex(None, true);
+        assert_eq!(tex, "\\font 100\\% \\hfill \\kern1em \\$5");
+        let cosmic = column.to_cosmic(column.span.0.len());
+        assert_eq!(cosmic.len(), 3);
+        assert_eq!(&cosmic[0].0, "100% ");
+        assert_eq!(&cosmic[1].0, "—");
+        assert_eq!(
+            cosmic[1].1.metrics_opt.unwrap(),
+            Metrics::new(11., 13.).into()
+        );
+        assert_eq!(&cosmic[2].0, " $5");
+        // Placeholders in the source text aren't restored as raw TeX.
+        let column = get_raw_tex_column(
+            "a\u{E000}7\u{E001}b \u{E000}99999999999999999999\u{E001} `tex:\\hfill`",
+        );
+        assert_eq!(
+            column.to_tex(None, true),
+            "\\font a7b 99999999999999999999 \\hfill"
+        );
+    }
+
+    #[test]
+    fn test_feature_scales() {
+        let mut column = get_column("an offer of coffee");
+        column.cosmic_font = CosmicFont::default_center();
+        column.set_feature_scales();
+        assert!(column.feature_scales.is_empty());
+        column.cosmic_font.features = CosmicFont::parse_features(&["-liga".to_string()])
+            .unwrap()
+            .0;
+        column.set_feature_scales();
+        assert_eq!(
+            column.feature_scales.keys().collect::<Vec<&usize>>(),
+            [&1, &3]
+        );
+        // Words whose widths change are measured separately, at a larger size.
+        let cosmic = column.to_cosmic(column.span.0.len());
+        assert_eq!(
+            cosmic
+                .iter()
+                .map(|(s, _)| s.as_str())
+                .collect::<Vec<&str>>(),
+            ["an ", "offer", " of ", "coffee"]
+        );
+        assert!(cosmic[1].1.metrics_opt.is_some());
+        assert!(cosmic[2].1.metrics_opt.is_none());
+    }
+
+    fn get_raw_tex_column(md: &str) -> SpanColumn {
+        SpanColumn::new(
+            Span::from_md(md, true).unwrap(),
+            CosmicFont::default_left(),
+            "\\font",
+        )
     }
 
     fn get_column(md: &str) -> SpanColumn {
         SpanColumn::new(
-            Span::from_md(md).unwrap(),
+            Span::from_md(md, false).unwrap(),
             CosmicFont::default_left(),
             "\\font",
         )
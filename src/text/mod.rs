@@ -1,6 +1,8 @@
 pub use daf::Daf;
 pub use source_text::SourceText;
+pub use title::{Cartouche, Frame, Title};
 
 mod daf;
 mod raw_text;
 mod source_text;
+mod title;
@@ -0,0 +1,252 @@
+use std::path::PathBuf;
+
+use lazy_static::lazy_static;
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+
+use crate::{page::Numbering, table::span_column::SpanColumn, tex};
+
+lazy_static! {
+    /// A run of Hebrew text: Hebrew letters, and the spaces and punctuation between them.
+    static ref RE_HEBREW: Regex = Regex::new(r"\p{Hebrew}(?:[\p{Hebrew}\s\p{P}]*\p{Hebrew})?").unwrap();
+}
+
+/// The title of the page.
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum Title {
+    /// A single line of text.
+    Text(String),
+    /// A title, subtitle, and chapter line, with an optional frame.
+    Cartouche(Cartouche),
+}
+
+impl Title {
+    /// Returns the TeX of the title.
+    /// `title_font` is the command that sets the title font. If None, the title is set in a huge version of the center font.
+    pub(crate) fn to_tex(&self, title_font: Option<&str>) -> String {
+        match self {
+            Self::Text(title) => format!(
+                "{}{}{}",
+                tex!("begin", "center"),
+                Self::get_title_line(title, title_font),
+                tex!("end", "center")
+            ),
+            Self::Cartouche(cartouche) => cartouche.to_tex(title_font),
+        }
+    }
+
+    /// Returns true if the title has Hebrew numerals or Hebrew text.
+    pub(crate) fn requires_hebrew(&self) -> bool {
+        match self {
+            Self::Text(_) => false,
+            Self::Cartouche(cartouche) => {
+                (cartouche.chapter_number.is_some() && cartouche.numbering.is_hebrew())
+                    || [
+                        Some(&cartouche.title),
+                        cartouche.subtitle.as_ref(),
+                        cartouche.chapter.as_ref(),
+                    ]
+                    .into_iter()
+                    .flatten()
+                    .any(|text| RE_HEBREW.is_match(text))
+            }
+        }
+    }
+
+    /// Returns the main line of the title.
+    /// \centerfont{\huge{Talmudifier}} or: {\titlefont Talmudifier}
+    fn get_title_line(title: &str, title_font: Option<&str>) -> String {
+        match title_font {
+            Some(title_font) => format!("{{{} {}}}", title_font, title),
+            None => format!("\\centerfont{{{}}}", tex!("huge", title)),
+        }
+    }
+}
+
+/// The frame of a [`Cartouche`].
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+pub enum Frame {
+    /// No frame.
+    #[default]
+    None,
+    /// A single rule.
+    Single,
+    /// A double rule.
+    Double,
+    /// An image file that is stretched to the size of the cartouche. The text is drawn over the image.
+    Image(PathBuf),
+}
+
+/// A decorative title block, as in the title pages of the Vilna Shas.
+///
+/// The cartouche is typeset in the center column of the opening block.
+/// The number of lines that it fills is measured, so the commentaries wrap around it.
+#[derive(Clone, Default, Deserialize, Serialize)]
+pub struct Cartouche {
+    /// The main title, e.g. the name of the tractate. This is set in the title font.
+    pub title: String,
+    /// An optional subtitle.
+    #[serde(default)]
+    pub subtitle: Option<String>,
+    /// The optional name of the chapter.
+    #[serde(default)]
+    pub chapter: Option<String>,
+    /// The optional chapter number. This is written before the name of the chapter.
+    #[serde(default)]
+    pub chapter_number: Option<u32>,
+    /// How the chapter number is written.
+    #[serde(default)]
+    pub numbering: Numbering,
+    /// The frame around the cartouche.
+    #[serde(default)]
+    pub frame: Frame,
+}
+
+impl Cartouche {
+    pub fn new<S: ToString>(title: S) -> Self {
+        Self {
+            title: title.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Set the subtitle.
+    pub fn subtitle<S: ToString>(mut self, subtitle: S) -> Self {
+        self.subtitle = Some(subtitle.to_string());
+        self
+    }
+
+    /// Set the name and number of the chapter.
+    pub fn chapter<S: ToString>(mut self, chapter: S, number: Option<u32>) -> Self {
+        self.chapter = Some(chapter.to_string());
+        self.chapter_number = number;
+        self
+    }
+
+    /// Set how the chapter number is written.
+    pub fn numbering(mut self, numbering: Numbering) -> Self {
+        self.numbering = numbering;
+        self
+    }
+
+    /// Set the frame.
+    pub fn frame(mut self, frame: Frame) -> Self {
+        self.frame = frame;
+        self
+    }
+
+    /// Returns the TeX of `text`. Runs of Hebrew text are set right to left, and the text is sanitized.
+    fn get_text(text: &str) -> String {
+        let mut tex = RE_HEBREW
+            .replace_all(text, |captures: &Captures| {
+                format!("\\texthebrew{{{}}}", &captures[0])
+            })
+            .to_string();
+        SpanColumn::santitize_tex(&mut tex);
+        tex
+    }
+
+    fn to_tex(&self, title_font: Option<&str>) -> String {
+        let mut lines = vec![Title::get_title_line(
+            &Self::get_text(&self.title),
+            title_font,
+        )];
+        if let Some(subtitle) = &self.subtitle {
+            lines.push(format!(
+                "{{\\centerfont\\large {}}}",
+                Self::get_text(subtitle)
+            ));
+        }
+        let chapter = self
+            .chapter_number
+            .map(|number| self.numbering.wrap(&self.numbering.format(number)))
+            .into_iter()
+            .chain(self.chapter.as_deref().map(Self::get_text))
+            .collect::<Vec<String>>();
+        if !chapter.is_empty() {
+            lines.push(format!("{{\\centerfont {}}}", chapter.join(" ")));
+        }
+        let lines = lines.join("\\par\n");
+        // The width of the text inside the frame.
+        let width = match self.frame {
+            Frame::Single => "\\dimexpr\\linewidth - 2\\fboxsep - 2\\fboxrule\\relax",
+            Frame::Double => "\\dimexpr\\linewidth - 4\\fboxsep - 4\\fboxrule\\relax",
+            Frame::None | Frame::Image(_) => "\\linewidth",
+        };
+        let minipage = format!(
+            "{}\\centering\n{}\n{}",
+            tex!("begin", "minipage", width),
+            lines,
+            tex!("end", "minipage")
+        );
+        match &self.frame {
+            Frame::None => format!("\\noindent{}", minipage),
+            Frame::Single => format!("\\noindent{}", tex!("fbox", minipage)),
+            Frame::Double => format!("\\noindent{}", tex!("fbox", tex!("fbox", minipage))),
+            // Draw the image behind the text, stretched to the size of the text.
+            Frame::Image(path) => format!(
+                "\\setbox2\\hbox{{{}}}\\noindent\\rlap{{\\lower\\dp2\\hbox{{\\XeTeXpicfile \"{}\" width \\wd2 height \\dimexpr\\ht2 + \\dp2\\relax}}}}\\box2",
+                minipage,
+                path.to_string_lossy().replace('\\', "/")
+            ),
+        }
+    }
+}
+
+impl From<Cartouche> for Title {
+    fn from(value: Cartouche) -> Self {
+        Self::Cartouche(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::page::Numbering;
+
+    use super::{Cartouche, Frame, Title};
+
+    #[test]
+    fn test_title() {
+        let title: Title = serde_json::from_str(r#""Talmudifier""#).unwrap();
+        assert_eq!(
+            title.to_tex(None),
+            "\\begin{center}\\centerfont{\\huge{Talmudifier}}\\end{center}"
+        );
+        assert_eq!(
+            title.to_tex(Some("\\titlefont")),
+            "\\begin{center}{\\titlefont Talmudifier}\\end{center}"
+        );
+        assert!(!title.requires_hebrew());
+
+        let title: Title = serde_json::from_str(
+            r#"{"title": "Berakhot", "subtitle": "Babylonian Talmud", "chapter": "Me'eimatai", "chapter_number": 1, "frame": "Double"}"#,
+        )
+        .unwrap();
+        let tex = title.to_tex(None);
+        assert!(tex.starts_with("\\noindent\\fbox{\\fbox{\\begin{minipage}{\\dimexpr\\linewidth - 4\\fboxsep - 4\\fboxrule\\relax}\\centering\n"));
+        assert!(tex.contains("\\centerfont{\\huge{Berakhot}}\\par\n{\\centerfont\\large Babylonian Talmud}\\par\n{\\centerfont {1} Me'eimatai}\n"));
+        assert!(!title.requires_hebrew());
+
+        let title = Title::from(
+            Cartouche::new("ברכות")
+                .chapter("מאימתי", Some(1))
+                .numbering(Numbering::Hebrew)
+                .frame(Frame::Image("frames/vilna.png".into())),
+        );
+        assert!(title.requires_hebrew());
+        let tex = title.to_tex(Some("\\titlefont"));
+        assert!(tex.contains("{\\titlefont \\texthebrew{ברכות}}"));
+        assert!(tex.contains("{\\centerfont \\texthebrew{א׳} \\texthebrew{מאימתי}}"));
+        assert!(tex.contains("\\XeTeXpicfile \"frames/vilna.png\" width \\wd2"));
+        assert!(tex.starts_with("\\setbox2\\hbox{\\begin{minipage}{\\linewidth}\\centering\n"));
+        assert!(tex.ends_with("\\relax}}\\box2"));
+
+        // The text is sanitized, and only the Hebrew text is set right to left.
+        let title = Title::from(Cartouche::new("Bava_Kamma").subtitle("50% & מסכת בבא קמא #1"));
+        assert!(title.requires_hebrew());
+        let tex = title.to_tex(None);
+        assert!(tex.contains("\\centerfont{\\huge{Bava\\_Kamma}}"));
+        assert!(tex.contains("{\\centerfont\\large 50\\% \\& \\texthebrew{מסכת בבא קמא} \\#1}"));
+    }
+}
@@ -10,7 +10,7 @@ mod pdf;
 mod xdv;
 
 pub use pdf::get_pdf;
-pub use xdv::get_num_lines;
+pub use xdv::{get_num_grid_lines, get_num_lines};
 
 #[cfg(feature = "textest")]
 pub use xdv::latex_to_xdv;
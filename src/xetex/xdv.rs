@@ -13,6 +13,8 @@
 //! https://github.com/mgieseki/dvisvgm/
 //! https://github.com/richard-uk1/dvi-rs/
 
+use std::{cmp::Reverse, collections::BTreeMap};
+
 use crate::error::Error;
 use nom::{
     bytes::streaming::tag,
@@ -74,11 +76,21 @@ impl<'t> Xdv<'t> {
     }
 
     /// Count the number of line breaks, separated by page breaks.
-    fn get_num_lines(mut self) -> Vec<usize> {
+    ///
+    /// If `grid` is not None, it is the distance between grid lines in sp.
+    /// Instead of counting down-moves, this counts the number of grid lines that have glyphs on them.
+    fn get_num_lines(mut self, grid: Option<i32>) -> Vec<usize> {
         let mut num_lines_per_page = vec![];
         let mut num_lines = 1;
         let mut got_words = false;
         let mut down = 0;
+        // The vertical position, and the y and z spacing.
+        let mut v = 0;
+        let mut y = 0;
+        let mut z = 0;
+        let mut stack = vec![];
+        // The vertical positions of glyphs on this page.
+        let mut baselines = vec![];
         while self.data.len() > 1 {
             // https://github.com/richard-uk1/dvi-rs/blob/c8078c37065fe7b72b09586c10ee220a7c91d99b/src/parser.rs#L12
             // Get the op code.
@@ -97,21 +109,39 @@ impl<'t> Xdv<'t> {
                 // Nop
                 138 => (),
                 // Bop
-                139 => self.advance(44),
+                139 => {
+                    self.advance(44);
+                    v = 0;
+                    y = 0;
+                    z = 0;
+                    stack.clear();
+                }
                 // Eop
                 140 => {
-                    // If there was a net down, add another line.
-                    // This seems to happen only when there are 2 lines.
-                    if down > 0 {
-                        num_lines += 1;
+                    match grid {
+                        Some(grid) => num_lines = count_grid_lines(&baselines, grid),
+                        None => {
+                            // If there was a net down, add another line.
+                            // This seems to happen only when there are 2 lines.
+                            if down > 0 {
+                                num_lines += 1;
+                            }
+                        }
                     }
                     num_lines_per_page.push(num_lines);
                     num_lines = 1;
                     got_words = false;
                     down = 0;
+                    baselines.clear();
+                }
+                // Push
+                141 => stack.push((v, y, z)),
+                // Pop
+                142 => {
+                    if let Some(state) = stack.pop() {
+                        (v, y, z) = state;
+                    }
                 }
-                // Push and Pop
-                141 | 142 => (),
                 // Right
                 143..=146 => self.advance4(op, 146),
                 // RightBy and set W
@@ -119,27 +149,26 @@ impl<'t> Xdv<'t> {
                 // RightBy and set X
                 152..=156 => self.advance4(op, 156),
                 // Down
-                157 => {
-                    down += self.read_i8() as i32;
-                }
-                158 => {
-                    down += self.read_i16() as i32;
-                }
-                159 => {
-                    down += self.read_i24();
-                }
-                160 => {
-                    down += self.read_i32();
+                157..=160 => {
+                    let a = self.read_signed(op - 156);
+                    down += a;
+                    v += a;
                 }
                 // Down and set Y
                 161..=165 => {
                     num_lines += 1;
-                    self.advance4(op, 165)
+                    if op > 161 {
+                        y = self.read_signed(op - 161);
+                    }
+                    v += y;
                 }
                 // Down and set Z
                 166..=170 => {
                     num_lines += 1;
-                    self.advance4(op, 170)
+                    if op > 166 {
+                        z = self.read_signed(op - 166);
+                    }
+                    v += z;
                 }
                 // SetFont to i
                 171..=234 => (),
@@ -228,6 +257,7 @@ impl<'t> Xdv<'t> {
                 253 => {
                     if self.version == DviVersion::Xdv7 {
                         got_words = true;
+                        baselines.push(v);
                         // w[4]
                         self.advance(4);
                         let n = self.read_u16();
@@ -240,6 +270,7 @@ impl<'t> Xdv<'t> {
                 254 => {
                     if self.version == DviVersion::Xdv5 {
                         got_words = true;
+                        baselines.push(v);
                         // l[2]
                         let l = self.read_u16();
                         // chars[2 * l]
@@ -256,6 +287,9 @@ impl<'t> Xdv<'t> {
             };
         }
         if got_words {
+            if let Some(grid) = grid {
+                num_lines = count_grid_lines(&baselines, grid);
+            }
             num_lines_per_page.push(num_lines);
         }
         num_lines_per_page
@@ -309,6 +343,16 @@ impl<'t> Xdv<'t> {
         v
     }
 
+    /// Read a signed integer that is `len` bytes long.
+    fn read_signed(&mut self, len: u8) -> i32 {
+        match len {
+            1 => self.read_i8() as i32,
+            2 => self.read_i16() as i32,
+            3 => self.read_i24(),
+            _ => self.read_i32(),
+        }
+    }
+
     fn advance(&mut self, delta: usize) {
         self.data = &self.data[delta..];
     }
@@ -324,6 +368,36 @@ impl<'t> Xdv<'t> {
     }
 }
 
+/// Returns the number of grid lines, `grid` sp apart, from the topmost grid line that has glyphs to the bottommost one.
+/// Empty grid lines in between are counted because they still take up space.
+///
+/// The grid is aligned to the most common baseline, so raised or lowered glyphs, e.g. superscripts,
+/// are rounded to the nearest grid line instead of moving the grid.
+///
+/// A page without glyphs has one line, as it does when line breaks are counted.
+fn count_grid_lines(baselines: &[i32], grid: i32) -> usize {
+    let mut counts = BTreeMap::new();
+    for v in baselines {
+        *counts.entry(*v).or_insert(0) += 1;
+    }
+    // If baselines are equally common, the topmost is used.
+    match counts
+        .into_iter()
+        .max_by_key(|(v, count)| (*count, Reverse(*v)))
+    {
+        Some((origin, _)) => {
+            let lines = baselines
+                .iter()
+                .map(|v| ((v - origin) as f32 / grid as f32).round() as i32)
+                .collect::<Vec<i32>>();
+            let top = lines.iter().min().unwrap();
+            let bottom = lines.iter().max().unwrap();
+            (bottom - top + 1) as usize
+        }
+        None => 1,
+    }
+}
+
 /// This is mostly copied from tectonic's latex_to_pdf
 pub fn latex_to_xdv<T: AsRef<str>>(latex: T) -> tectonic::Result<Vec<u8>> {
     let mut status = NoopStatusBackend::default();
@@ -371,20 +445,30 @@ pub fn latex_to_xdv<T: AsRef<str>>(latex: T) -> tectonic::Result<Vec<u8>> {
 
 pub fn get_num_lines<T: AsRef<str>>(latex: T) -> Result<Vec<usize>, Error> {
     match latex_to_xdv(latex) {
-        Ok(data) => Ok(Xdv::new(&data).get_num_lines()),
+        Ok(data) => Ok(Xdv::new(&data).get_num_lines(None)),
+        Err(error) => Err(Error::Xdv(error)),
+    }
+}
+
+/// Returns the number of lines per page on a baseline grid. `skip` is the distance between grid lines in pts.
+pub fn get_num_grid_lines<T: AsRef<str>>(latex: T, skip: f32) -> Result<Vec<usize>, Error> {
+    // XeTeX's DVI units are sp.
+    let grid = (skip * 65536.).round() as i32;
+    match latex_to_xdv(latex) {
+        Ok(data) => Ok(Xdv::new(&data).get_num_lines(Some(grid))),
         Err(error) => Err(Error::Xdv(error)),
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::Xdv;
+    use super::{count_grid_lines, Xdv};
 
     #[test]
     fn test_xdv_four_lines() {
         let data = include_bytes!("../../test_text/four_lines.xdv").to_vec();
         let xdv = Xdv::new(&data);
-        let lines = xdv.get_num_lines();
+        let lines = xdv.get_num_lines(None);
         assert_eq!(lines.len(), 20);
         [4, 4, 4, 4, 5, 5, 5, 5, 5, 5, 5, 6, 6, 6, 6, 6, 6, 6, 6, 7]
             .into_iter()
@@ -398,7 +482,7 @@ mod tests {
     fn test_xdv_one_line() {
         let data = include_bytes!("../../test_text/one_line.xdv").to_vec();
         let xdv = Xdv::new(&data);
-        let lines = xdv.get_num_lines();
+        let lines = xdv.get_num_lines(None);
         assert_eq!(lines.len(), 20);
         let q = lines.clone();
         let expected_line_counts = vec![1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 4, 5, 5, 5, 6, 6, 6];
@@ -412,4 +496,35 @@ mod tests {
                 assert_eq!(expected, line, "index {} lines {:?}", i, q);
             });
     }
+
+    #[test]
+    fn test_xdv_grid_lines() {
+        assert_eq!(count_grid_lines(&[], 100), 1);
+        // Glyphs on the same baseline, glyphs that are slightly off the grid, and an empty grid line.
+        assert_eq!(count_grid_lines(&[500, 500, 600, 610, 790, 800], 100), 4);
+        assert_eq!(count_grid_lines(&[500, 600, 700, 800], 100), 4);
+        // A superscript on the first line and a subscript on the second line don't move the grid.
+        assert_eq!(count_grid_lines(&[470, 500, 500, 600, 600, 625], 100), 2);
+        // A glyph that is closer to the next grid line is on that line.
+        assert_eq!(count_grid_lines(&[500, 500, 600, 600, 670], 100), 3);
+
+        // The test files have no extra space between lines, so the grid lines are the same as the line breaks.
+        for data in [
+            include_bytes!("../../test_text/one_line.xdv").to_vec(),
+            include_bytes!("../../test_text/four_lines.xdv").to_vec(),
+        ] {
+            assert_eq!(
+                Xdv::new(&data).get_num_lines(Some(13 * 65536)),
+                Xdv::new(&data).get_num_lines(None)
+            );
+        }
+        // An empty page has the same number of lines in both modes.
+        let mut data = vec![247, 7];
+        data.extend([0; 13]);
+        data.push(139);
+        data.extend([0; 44]);
+        data.extend([140, 223]);
+        assert_eq!(Xdv::new(&data).get_num_lines(None), vec![1]);
+        assert_eq!(Xdv::new(&data).get_num_lines(Some(100)), vec![1]);
+    }
 }